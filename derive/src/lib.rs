@@ -0,0 +1,155 @@
+//! Derive macros for the `subsquid-data-streaming` crate.
+//!
+//! These are re-exported by the main crate and should be used through it.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+use type_name::check_type_name;
+
+mod type_name;
+
+/// Derives `EvmEvent` and `TryFrom<&LogEntry>` for a struct with named fields.
+///
+/// The event name defaults to the struct name and can be overridden with
+/// `#[abi(name = "...")]` on the struct. Each field becomes an event parameter of the same name,
+/// whose Solidity type is inferred from the Rust type through `SolType` or given with
+/// `#[abi(type = "...")]` on the field. Fields marked `#[indexed]` are read from topics.
+#[proc_macro_derive(EvmEvent, attributes(indexed, abi))]
+pub fn derive_evm_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_evm_event(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_evm_event(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let mut event_name = ident.to_string();
+    let mut anonymous = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("abi")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                event_name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else if meta.path.is_ident("anonymous") {
+                anonymous = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"` or `anonymous`"))
+            }
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "EvmEvent can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "EvmEvent can only be derived for structs",
+            ))
+        }
+    };
+
+    // The event description is cached in a static, which would be shared by every
+    // instantiation of a generic type.
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "EvmEvent cannot be derived for generic structs",
+        ));
+    }
+
+    let krate = quote!(::subsquid_data_streaming);
+    let mut params = Vec::new();
+    let mut decoders = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let name = field_ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name).to_string();
+        let indexed = field.attrs.iter().any(|a| a.path().is_ident("indexed"));
+
+        let mut type_override = None;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("abi")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("type") {
+                    let type_name = meta.value()?.parse::<LitStr>()?;
+                    check_type_name(&type_name.value())
+                        .map_err(|message| syn::Error::new(type_name.span(), message))?;
+                    type_override = Some(type_name);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `type = \"...\"`"))
+                }
+            })?;
+        }
+
+        let kind = match type_override {
+            Some(type_name) => quote! {
+                #krate::abi::ParamType::parse(#type_name, &[])
+                    .expect(concat!("invalid ABI type for field `", #name, "`"))
+            },
+            None => quote!(<#ty as #krate::abi::SolType>::param_type()),
+        };
+        params.push(quote! {
+            #krate::abi::EventParam {
+                name: #name.to_string(),
+                kind: #kind,
+                indexed: #indexed,
+            }
+        });
+        decoders.push(quote! {
+            #field_ident: <#ty as #krate::abi::SolType>::from_value(
+                params.next().ok_or_else(|| {
+                    #krate::DataStreamError::DecodingError(
+                        concat!("missing parameter `", #name, "`").to_string(),
+                    )
+                })?.value,
+            )?
+        });
+    }
+
+    Ok(quote! {
+        impl #krate::abi::EvmEvent for #ident {
+            fn event() -> &'static #krate::abi::Event {
+                static EVENT: ::std::sync::OnceLock<#krate::abi::Event> = ::std::sync::OnceLock::new();
+                EVENT.get_or_init(|| #krate::abi::Event {
+                    name: #event_name.to_string(),
+                    inputs: ::std::vec![#(#params),*],
+                    anonymous: #anonymous,
+                })
+            }
+
+            fn topic0() -> #krate::B256 {
+                static TOPIC0: ::std::sync::OnceLock<#krate::B256> = ::std::sync::OnceLock::new();
+                *TOPIC0.get_or_init(|| <Self as #krate::abi::EvmEvent>::event().topic0())
+            }
+
+            fn from_event(
+                event: #krate::abi::DecodedEvent,
+            ) -> ::std::result::Result<Self, #krate::DataStreamError> {
+                let mut params = event.params.into_iter();
+                ::std::result::Result::Ok(Self {
+                    #(#decoders),*
+                })
+            }
+        }
+
+        impl ::std::convert::TryFrom<&#krate::LogEntry> for #ident {
+            type Error = #krate::DataStreamError;
+
+            fn try_from(log: &#krate::LogEntry) -> ::std::result::Result<Self, Self::Error> {
+                <Self as #krate::abi::EvmEvent>::decode_log(log)
+            }
+        }
+    })
+}
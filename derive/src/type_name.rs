@@ -0,0 +1,40 @@
+//! The ABI type name grammar of `ParamType::parse`, checked at compile time.
+//!
+//! The main crate includes this file in its tests to check that both accept the same type
+//! names, so the two cannot drift apart.
+
+/// Checks a type given with `#[abi(type = "...")]` by the rules of `ParamType::parse`, so that
+/// a typo fails to compile instead of panicking in `event()`.
+///
+/// Tuples need their component types, which a single type string cannot carry, so they are
+/// rejected.
+pub(crate) fn check_type_name(type_name: &str) -> Result<(), String> {
+    let invalid = || format!("invalid ABI type `{}`", type_name);
+    let mut element = type_name;
+    while let Some(inner) = element.strip_suffix(']') {
+        let open = inner.rfind('[').ok_or_else(invalid)?;
+        let size = &inner[open + 1..];
+        if !size.is_empty() && size.parse::<usize>().is_err() {
+            return Err(invalid());
+        }
+        element = &inner[..open];
+    }
+
+    let width = |prefix: &str| element.strip_prefix(prefix).map(str::parse::<usize>);
+    match element {
+        "address" | "bool" | "bytes" | "string" | "uint" | "int" => Ok(()),
+        _ if element.starts_with("tuple") || element.starts_with('(') => Err(format!(
+            "tuple type `{}` cannot be given with `#[abi(type = \"...\")]`, \
+             implement `SolType` for the field type instead",
+            type_name
+        )),
+        _ => match width("uint").or_else(|| width("int")) {
+            Some(Ok(size)) if size % 8 == 0 && (8..=256).contains(&size) => Ok(()),
+            Some(_) => Err(invalid()),
+            None => match width("bytes") {
+                Some(Ok(size)) if (1..=32).contains(&size) => Ok(()),
+                _ => Err(invalid()),
+            },
+        },
+    }
+}
@@ -18,11 +18,11 @@ async fn main() {
         end_block
     );
 
-    let data_stream = DataStream::new()
+    let logs = DataStream::new()
         .set_data_source(DataSource::Subsquid(
             "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
         ))
-        .add_log_filter(LogFilter::new().with_topic(topic))
+        .add_log_filter(LogFilter::new().with_topic(topic).expect("Invalid topic"))
         .select_log_fields(LogFields {
             topic0: true,
             data: true,
@@ -32,25 +32,19 @@ async fn main() {
         .to_block(end_block)
         .build()
         .await
-        .expect("Failed to build DataStream");
+        .expect("Failed to build DataStream")
+        .logs();
 
-    tokio::pin!(data_stream);
+    tokio::pin!(logs);
 
-    while let Some(result) = data_stream.next().await {
+    while let Some(result) = logs.next().await {
         match result {
-            Ok(data_batch) => {
-                for item in data_batch {
-                    if let Some(logs) = item.logs {
-                        for log in logs {
-                            log::info!(
-                                "Received Log: Topics = {:?}, Tx Index = {:?}",
-                                log.topics,
-                                log.transaction_index
-                            );
-                        }
-                    }
-                }
-            }
+            Ok(log) => log::info!(
+                "Received Log: Block = {}, Topics = {:?}, Tx Index = {:?}",
+                log.block.number,
+                log.topics,
+                log.transaction_index
+            ),
             Err(e) => log::error!("Error: {:?}", e),
         }
     }
@@ -18,11 +18,15 @@ async fn main() {
         end_block
     );
 
-    let data_stream = DataStream::new()
+    let transactions = DataStream::new()
         .set_data_source(DataSource::Subsquid(
             "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
         ))
-        .add_tx_filter(TransactionFilter::new().with_from(sender))
+        .add_tx_filter(
+            TransactionFilter::new()
+                .with_from(sender)
+                .expect("Invalid sender address"),
+        )
         .select_tx_fields(TransactionFields {
             hash: true,
             to: true,
@@ -32,25 +36,19 @@ async fn main() {
         .to_block(end_block)
         .build()
         .await
-        .expect("Failed to build DataStream");
+        .expect("Failed to build DataStream")
+        .transactions();
 
-    tokio::pin!(data_stream);
+    tokio::pin!(transactions);
 
-    while let Some(result) = data_stream.next().await {
+    while let Some(result) = transactions.next().await {
         match result {
-            Ok(data_batch) => {
-                for item in data_batch {
-                    if let Some(transactions) = item.transactions {
-                        for tx in transactions {
-                            log::info!(
-                                "Received Transaction: Hash = {:?}, To Address = {:?}",
-                                tx.hash.unwrap_or_default(),
-                                tx.to.unwrap_or_default()
-                            );
-                        }
-                    }
-                }
-            }
+            Ok(tx) => log::info!(
+                "Received Transaction: Block = {}, Hash = {:?}, To Address = {:?}",
+                tx.block.number,
+                tx.hash.unwrap_or_default(),
+                tx.to.unwrap_or_default()
+            ),
             Err(e) => log::error!("Error: {:?}", e),
         }
     }
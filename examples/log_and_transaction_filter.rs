@@ -22,7 +22,7 @@ async fn main() {
         end_block
     );
 
-    let data_stream = DataStream::new()
+    let blocks = DataStream::new()
         .set_data_source(DataSource::Subsquid(
             "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
         ))
@@ -35,43 +35,44 @@ async fn main() {
             hash: true,
             ..Default::default()
         })
-        .add_log_filter(LogFilter::new().with_topic(topic))
-        .add_tx_filter(TransactionFilter::new().with_from(sender))
+        .add_log_filter(LogFilter::new().with_topic(topic).expect("Invalid topic"))
+        .add_tx_filter(
+            TransactionFilter::new()
+                .with_from(sender)
+                .expect("Invalid sender address"),
+        )
         .from_block(start_block)
         .to_block(end_block)
         .build()
         .await
-        .expect("Failed to build DataStream");
+        .expect("Failed to build DataStream")
+        .blocks();
 
-    tokio::pin!(data_stream);
+    tokio::pin!(blocks);
 
-    while let Some(result) = data_stream.next().await {
+    while let Some(result) = blocks.next().await {
         match result {
-            Ok(data_batch) => {
-                for item in data_batch {
-                    if let Some(logs) = item.logs {
-                        for log in logs {
-                            log::info!(
-                                "Received Log: Topics = {:?}, Data = {:?}, TransactionIndex = {:?}, LogIndex = {:?}",
-                                log.topics,
-                                log.data,
-                                log.transaction_index,
-                                log.log_index
-                            );
-                        }
-                    }
+            Ok(block) => {
+                log::info!("Received Block: Number = {}", block.header.number);
 
-                    if let Some(transactions) = item.transactions {
-                        for tx in transactions {
-                            log::info!(
-                                "Received Transaction: Hash = {:?}, Nonce = {:?}, TransactionIndex = {:?}, To = {:?}",
-                                tx.hash.unwrap_or_default(),
-                                tx.nonce.unwrap_or(0),
-                                tx.transaction_index.unwrap_or(0),
-                                tx.to.unwrap_or_default()
-                            );
-                        }
-                    }
+                for log in block.logs.unwrap_or_default() {
+                    log::info!(
+                        "Received Log: Topics = {:?}, Data = {:?}, TransactionIndex = {:?}, LogIndex = {:?}",
+                        log.topics,
+                        log.data,
+                        log.transaction_index,
+                        log.log_index
+                    );
+                }
+
+                for tx in block.transactions.unwrap_or_default() {
+                    log::info!(
+                        "Received Transaction: Hash = {:?}, Nonce = {:?}, TransactionIndex = {:?}, To = {:?}",
+                        tx.hash.unwrap_or_default(),
+                        tx.nonce.unwrap_or(0),
+                        tx.transaction_index.unwrap_or(0),
+                        tx.to.unwrap_or_default()
+                    );
                 }
             }
             Err(e) => log::error!("Error: {:?}", e),
@@ -0,0 +1,329 @@
+use super::event::{DecodedEvent, Event, EventParam};
+use super::function::{DecodedInput, Function, Param};
+use super::param_type::ParamType;
+use super::revert::{AbiError, Revert};
+use crate::errors::DataStreamError;
+use crate::models::{LogEntry, TraceEntry, TransactionEntry};
+use crate::types::B256;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A contract ABI, loaded from its JSON description.
+///
+/// Events, functions and custom errors are kept. Constructors, `receive` and `fallback`
+/// entries are ignored, as they cannot be identified from logs or calldata.
+#[derive(Clone, Debug, Default)]
+pub struct Abi {
+    /// The events declared in the ABI.
+    pub events: Vec<Event>,
+    /// The functions declared in the ABI.
+    pub functions: Vec<Function>,
+    /// The custom errors declared in the ABI.
+    pub errors: Vec<AbiError>,
+    /// Indexes into `events`, keyed by signature hash. Several events can share a hash when
+    /// they only differ in which parameters are indexed (e.g. ERC-20 and ERC-721 `Transfer`).
+    by_topic0: HashMap<B256, Vec<usize>>,
+    /// Indexes into `functions`, keyed by selector.
+    functions_by_selector: HashMap<[u8; 4], usize>,
+    /// Indexes into `errors`, keyed by selector.
+    errors_by_selector: HashMap<[u8; 4], usize>,
+}
+
+impl Abi {
+    /// Parses a JSON ABI.
+    ///
+    /// Accepts either the plain array of entries emitted by `solc`, or a build artifact object
+    /// with an `abi` key (as produced by Hardhat and Foundry).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the JSON is not a valid ABI.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::abi::Abi;
+    ///
+    /// let abi = Abi::from_json(r#"[{
+    ///     "type": "event",
+    ///     "name": "Transfer",
+    ///     "inputs": [
+    ///         {"name": "from", "type": "address", "indexed": true},
+    ///         {"name": "to", "type": "address", "indexed": true},
+    ///         {"name": "value", "type": "uint256", "indexed": false}
+    ///     ],
+    ///     "anonymous": false
+    /// }]"#)?;
+    /// assert_eq!(abi.events[0].signature(), "Transfer(address,address,uint256)");
+    /// # Ok::<(), subsquid_data_streaming::DataStreamError>(())
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, DataStreamError> {
+        let document: AbiDocument = serde_json::from_str(json)
+            .map_err(|e| DataStreamError::DecodingError(format!("invalid ABI JSON: {}", e)))?;
+        let entries = match document {
+            AbiDocument::Entries(entries) => entries,
+            AbiDocument::Artifact { abi } => abi,
+        };
+
+        let mut abi = Abi::default();
+        for entry in entries {
+            match entry.kind.as_str() {
+                "event" => {
+                    let inputs = entry
+                        .inputs
+                        .iter()
+                        .map(|param| {
+                            Ok(EventParam {
+                                name: param.name.clone(),
+                                kind: param.param_type()?,
+                                indexed: param.indexed,
+                            })
+                        })
+                        .collect::<Result<_, DataStreamError>>()?;
+                    abi.add_event(Event {
+                        name: entry.name,
+                        inputs,
+                        anonymous: entry.anonymous,
+                    });
+                }
+                "function" => abi.add_function(Function {
+                    inputs: params(&entry.inputs)?,
+                    outputs: params(&entry.outputs)?,
+                    name: entry.name,
+                }),
+                "error" => abi.add_error(AbiError {
+                    inputs: params(&entry.inputs)?,
+                    name: entry.name,
+                }),
+                _ => {}
+            }
+        }
+        Ok(abi)
+    }
+
+    /// Adds an event to the ABI.
+    pub fn add_event(&mut self, event: Event) {
+        if !event.anonymous {
+            self.by_topic0
+                .entry(event.topic0())
+                .or_default()
+                .push(self.events.len());
+        }
+        self.events.push(event);
+    }
+
+    /// Adds a function to the ABI. A function with the same selector is replaced.
+    pub fn add_function(&mut self, function: Function) {
+        self.functions_by_selector
+            .insert(function.selector(), self.functions.len());
+        self.functions.push(function);
+    }
+
+    /// Adds a custom error to the ABI. An error with the same selector is replaced.
+    pub fn add_error(&mut self, error: AbiError) {
+        self.errors_by_selector
+            .insert(error.selector(), self.errors.len());
+        self.errors.push(error);
+    }
+
+    /// Returns the first function called `name`.
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    /// Returns the first event called `name`.
+    pub fn event(&self, name: &str) -> Option<&Event> {
+        self.events.iter().find(|event| event.name == name)
+    }
+
+    /// Returns the signature hashes of all non-anonymous events, for use in a `LogFilter`.
+    pub fn topics(&self) -> Vec<B256> {
+        let mut topics: Vec<B256> = self.by_topic0.keys().copied().collect();
+        topics.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        topics
+    }
+
+    /// Finds the event matching the `topic0` of `log` and decodes it.
+    ///
+    /// Returns `Ok(None)` if no event in the ABI matches the log. Anonymous events are never
+    /// matched, as they cannot be identified from the log alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the log matches an event but cannot be
+    /// decoded with its layout.
+    pub fn decode_log(&self, log: &LogEntry) -> Result<Option<DecodedEvent>, DataStreamError> {
+        let Some(candidates) = log.topics.first().and_then(|t| self.by_topic0.get(t)) else {
+            return Ok(None);
+        };
+        let event = candidates
+            .iter()
+            .map(|&i| &self.events[i])
+            .find(|event| event.topic_count() == log.topics.len())
+            .unwrap_or(&self.events[candidates[0]]);
+        event.decode_log(log).map(Some)
+    }
+
+    /// Decodes calldata into a function call, matched by its 4-byte selector.
+    ///
+    /// Returns `Ok(None)` for calldata shorter than a selector, such as plain ether transfers,
+    /// and `DecodedInput::Unknown` with the selector when no function of the ABI matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the selector matches a function but the
+    /// arguments cannot be decoded with its layout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::abi::{Abi, DecodedInput};
+    ///
+    /// let abi = Abi::from_json(r#"[{"type": "function", "name": "pause", "inputs": []}]"#)?;
+    /// let input = abi.decode_input(&[0x84, 0x56, 0xcb, 0x59])?.unwrap();
+    /// assert_eq!(input.call().unwrap().name, "pause");
+    ///
+    /// let input = abi.decode_input(&[0xde, 0xad, 0xbe, 0xef])?.unwrap();
+    /// assert_eq!(input, DecodedInput::Unknown { selector: [0xde, 0xad, 0xbe, 0xef] });
+    /// # Ok::<(), subsquid_data_streaming::DataStreamError>(())
+    /// ```
+    pub fn decode_input(&self, input: &[u8]) -> Result<Option<DecodedInput>, DataStreamError> {
+        let Some(selector) = input.get(..4) else {
+            return Ok(None);
+        };
+        let selector: [u8; 4] = selector.try_into().unwrap();
+        match self.functions_by_selector.get(&selector) {
+            Some(&i) => self.functions[i]
+                .decode_input(input)
+                .map(|call| Some(DecodedInput::Known(call))),
+            None => Ok(Some(DecodedInput::Unknown { selector })),
+        }
+    }
+
+    /// Decodes the calldata of a transaction. See [`Abi::decode_input`].
+    ///
+    /// Returns `Ok(None)` if the `input` field was not selected or holds no selector.
+    pub fn decode_transaction(
+        &self,
+        transaction: &TransactionEntry,
+    ) -> Result<Option<DecodedInput>, DataStreamError> {
+        match &transaction.input {
+            Some(input) => self.decode_input(input),
+            None => Ok(None),
+        }
+    }
+
+    /// Decodes revert data into a reason string, a panic code or a custom error of the ABI.
+    ///
+    /// Data that matches none of these is returned as `Revert::Raw`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the selector is recognized but the
+    /// arguments cannot be decoded.
+    pub fn decode_revert(&self, data: &[u8]) -> Result<Revert, DataStreamError> {
+        Revert::decode(data, |selector| {
+            self.errors_by_selector
+                .get(&selector)
+                .map(|&i| self.errors[i].clone())
+        })
+    }
+
+    /// Decodes why a failed transaction reverted, using the traces of its block.
+    ///
+    /// The revert data is taken from the output of the top-level call trace of the transaction.
+    /// Returns `Ok(None)` if the transaction did not fail (its `status` is not 0 or was not
+    /// selected) or if its top-level trace is not among `traces`. Select the trace
+    /// `transaction_index`, `trace_address`, `error` and `call_result_output` fields and add a
+    /// trace filter covering the transaction for the data to be available.
+    pub fn decode_transaction_revert(
+        &self,
+        transaction: &TransactionEntry,
+        traces: &[TraceEntry],
+    ) -> Result<Option<Revert>, DataStreamError> {
+        if transaction.status != Some(0) {
+            return Ok(None);
+        }
+        let Some(root) = traces.iter().find(|trace| {
+            trace.transaction_index.is_some()
+                && trace.transaction_index == transaction.transaction_index
+                && trace.trace_address.as_deref() == Some(&[])
+        }) else {
+            return Ok(None);
+        };
+        match root
+            .result
+            .as_ref()
+            .and_then(|result| result.output.as_ref())
+        {
+            Some(output) => self.decode_revert(output).map(Some),
+            None => Ok(root.revert_reason.clone().map(Revert::Reason)),
+        }
+    }
+}
+
+/// Converts JSON ABI parameters into typed parameters.
+fn params(params: &[AbiParam]) -> Result<Vec<Param>, DataStreamError> {
+    params
+        .iter()
+        .map(|param| {
+            Ok(Param {
+                name: param.name.clone(),
+                kind: param.param_type()?,
+            })
+        })
+        .collect()
+}
+
+/// The top level of a JSON ABI document.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AbiDocument {
+    Entries(Vec<AbiEntry>),
+    Artifact { abi: Vec<AbiEntry> },
+}
+
+/// A single entry of a JSON ABI.
+#[derive(Deserialize)]
+struct AbiEntry {
+    #[serde(rename = "type", default = "default_kind")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<AbiParam>,
+    #[serde(default)]
+    outputs: Vec<AbiParam>,
+    #[serde(default)]
+    anonymous: bool,
+}
+
+/// A parameter of a JSON ABI entry.
+#[derive(Deserialize)]
+struct AbiParam {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    indexed: bool,
+    #[serde(default)]
+    components: Vec<AbiParam>,
+}
+
+impl AbiParam {
+    /// Resolves the parameter type, including tuple components.
+    fn param_type(&self) -> Result<ParamType, DataStreamError> {
+        let components = self
+            .components
+            .iter()
+            .map(AbiParam::param_type)
+            .collect::<Result<Vec<_>, _>>()?;
+        ParamType::parse(&self.kind, &components)
+    }
+}
+
+/// Entries without a `type` are functions, per the Solidity ABI specification.
+fn default_kind() -> String {
+    "function".to_string()
+}
@@ -0,0 +1,121 @@
+use super::param_type::ParamType;
+use super::value::Value;
+use crate::errors::DataStreamError;
+use crate::types::{Address, Bytes, U256};
+
+/// Decodes ABI-encoded `data` as a tuple of `types`, e.g. the non-indexed parameters of an event.
+pub(crate) fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Value>, DataStreamError> {
+    decode_tuple(types, data, 0)
+}
+
+/// Decodes a single static value from a 32-byte topic.
+pub(crate) fn decode_word(kind: &ParamType, word: &[u8; 32]) -> Result<Value, DataStreamError> {
+    decode_value(kind, word, 0)
+}
+
+/// Decodes a tuple whose head starts at `base`. Offsets of dynamic members are relative to `base`.
+fn decode_tuple(
+    types: &[ParamType],
+    data: &[u8],
+    base: usize,
+) -> Result<Vec<Value>, DataStreamError> {
+    let mut offset = base;
+    let mut values = Vec::with_capacity(types.len());
+    for kind in types {
+        let value = if kind.is_dynamic() {
+            let pointer = read_usize(data, offset)?;
+            let at = base
+                .checked_add(pointer)
+                .ok_or_else(|| error("offset overflows"))?;
+            decode_value(kind, data, at)?
+        } else {
+            decode_value(kind, data, offset)?
+        };
+        values.push(value);
+        offset += kind.head_size();
+    }
+    Ok(values)
+}
+
+/// Decodes a value of type `kind` located at `at`.
+fn decode_value(kind: &ParamType, data: &[u8], at: usize) -> Result<Value, DataStreamError> {
+    match kind {
+        ParamType::Address => {
+            let word = read_word(data, at)?;
+            Ok(Value::Address(Address::from_slice(&word[12..])?))
+        }
+        ParamType::Bool => match U256::from_be_bytes(read_word(data, at)?).to_u64() {
+            Some(0) => Ok(Value::Bool(false)),
+            Some(1) => Ok(Value::Bool(true)),
+            _ => Err(error("invalid bool")),
+        },
+        ParamType::Uint(_) => Ok(Value::Uint(U256::from_be_bytes(read_word(data, at)?))),
+        ParamType::Int(_) => Ok(Value::Int(U256::from_be_bytes(read_word(data, at)?))),
+        ParamType::FixedBytes(size) => {
+            Ok(Value::FixedBytes(read_word(data, at)?[..*size].to_vec()))
+        }
+        ParamType::Bytes => Ok(Value::Bytes(Bytes::from(read_dynamic_bytes(data, at)?))),
+        ParamType::String => {
+            let bytes = read_dynamic_bytes(data, at)?;
+            String::from_utf8(bytes.to_vec())
+                .map(Value::String)
+                .map_err(|_| error("invalid UTF-8 in string"))
+        }
+        ParamType::Array(element) => {
+            let length = read_usize(data, at)?;
+            check_length(data, at + 32, length, element)?;
+            let types = vec![(**element).clone(); length];
+            decode_tuple(&types, data, at + 32).map(Value::Array)
+        }
+        ParamType::FixedArray(element, size) => {
+            let types = vec![(**element).clone(); *size];
+            decode_tuple(&types, data, at).map(Value::FixedArray)
+        }
+        ParamType::Tuple(components) => decode_tuple(components, data, at).map(Value::Tuple),
+    }
+}
+
+/// Reads the 32-byte word at `at`.
+fn read_word(data: &[u8], at: usize) -> Result<[u8; 32], DataStreamError> {
+    at.checked_add(32)
+        .and_then(|end| data.get(at..end))
+        .map(|word| word.try_into().unwrap())
+        .ok_or_else(|| error("data too short"))
+}
+
+/// Reads the word at `at` as an offset or length.
+fn read_usize(data: &[u8], at: usize) -> Result<usize, DataStreamError> {
+    U256::from_be_bytes(read_word(data, at)?)
+        .to_u64()
+        .and_then(|value| usize::try_from(value).ok())
+        .ok_or_else(|| error("offset or length too large"))
+}
+
+/// Reads a length-prefixed byte string located at `at`.
+fn read_dynamic_bytes(data: &[u8], at: usize) -> Result<&[u8], DataStreamError> {
+    let length = read_usize(data, at)?;
+    let start = at + 32;
+    start
+        .checked_add(length)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| error("data too short"))
+}
+
+/// Rejects array lengths that cannot possibly fit in the remaining data, before allocating.
+fn check_length(
+    data: &[u8],
+    start: usize,
+    length: usize,
+    element: &ParamType,
+) -> Result<(), DataStreamError> {
+    let needed = length.checked_mul(element.head_size());
+    match needed {
+        Some(needed) if start.saturating_add(needed) <= data.len() => Ok(()),
+        _ => Err(error("array length exceeds data")),
+    }
+}
+
+/// Builds a decoding error.
+fn error(reason: &str) -> DataStreamError {
+    DataStreamError::DecodingError(format!("invalid ABI encoding: {}", reason))
+}
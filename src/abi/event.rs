@@ -0,0 +1,153 @@
+use super::decode::{decode, decode_word};
+use super::param_type::ParamType;
+use super::value::Value;
+use crate::errors::DataStreamError;
+use crate::models::LogEntry;
+use crate::types::{keccak256, B256};
+
+/// An event declared in a contract ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The event name, e.g. `Transfer`.
+    pub name: String,
+    /// The event parameters, in declaration order.
+    pub inputs: Vec<EventParam>,
+    /// Whether the event is anonymous, i.e. emitted without a signature topic.
+    pub anonymous: bool,
+}
+
+/// A parameter of an [`Event`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventParam {
+    /// The parameter name. May be empty.
+    pub name: String,
+    /// The parameter type.
+    pub kind: ParamType,
+    /// Whether the parameter is stored in a topic rather than in the log data.
+    pub indexed: bool,
+}
+
+impl Event {
+    /// Returns the canonical signature, e.g. `Transfer(address,address,uint256)`.
+    pub fn signature(&self) -> String {
+        let types: Vec<String> = self.inputs.iter().map(|p| p.kind.to_string()).collect();
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    /// Returns the signature hash stored in `topic0` of non-anonymous logs.
+    pub fn topic0(&self) -> B256 {
+        keccak256(self.signature())
+    }
+
+    /// Returns the number of topics a log of this event carries.
+    pub(crate) fn topic_count(&self) -> usize {
+        let indexed = self.inputs.iter().filter(|p| p.indexed).count();
+        if self.anonymous {
+            indexed
+        } else {
+            indexed + 1
+        }
+    }
+
+    /// Decodes the topics and data of `log` into named parameters.
+    ///
+    /// Indexed parameters of dynamic types (strings, bytes, arrays and tuples) are only stored as
+    /// the Keccak-256 hash of their value, so they are returned as `Value::FixedBytes` holding
+    /// that hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the log does not match the event layout.
+    pub fn decode_log(&self, log: &LogEntry) -> Result<DecodedEvent, DataStreamError> {
+        if log.topics.len() != self.topic_count() {
+            return Err(DataStreamError::DecodingError(format!(
+                "log has {} topics, event {} expects {}",
+                log.topics.len(),
+                self.signature(),
+                self.topic_count()
+            )));
+        }
+
+        let mut topics = log.topics.iter().skip(usize::from(!self.anonymous));
+        let data_types: Vec<ParamType> = self
+            .inputs
+            .iter()
+            .filter(|p| !p.indexed)
+            .map(|p| p.kind.clone())
+            .collect();
+        let data: &[u8] = match &log.data {
+            Some(data) => data,
+            None if data_types.is_empty() => &[],
+            None => {
+                return Err(DataStreamError::DecodingError(format!(
+                    "cannot decode {} without the log data, select `LogFields::data`",
+                    self.signature()
+                )))
+            }
+        };
+        let mut data_values = decode(&data_types, data)?.into_iter();
+
+        let mut params = Vec::with_capacity(self.inputs.len());
+        for input in &self.inputs {
+            let value = if input.indexed {
+                let topic = topics.next().expect("topic count checked above");
+                if input.kind.is_dynamic() {
+                    Value::FixedBytes(topic.as_bytes().to_vec())
+                } else {
+                    decode_word(&input.kind, topic.as_bytes())?
+                }
+            } else {
+                data_values.next().expect("one value per data type")
+            };
+            params.push(DecodedParam {
+                name: input.name.clone(),
+                value,
+                indexed: input.indexed,
+            });
+        }
+
+        Ok(DecodedEvent {
+            name: self.name.clone(),
+            params,
+        })
+    }
+}
+
+/// An event decoded from a log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedEvent {
+    /// The event name.
+    pub name: String,
+    /// The decoded parameters, in declaration order.
+    pub params: Vec<DecodedParam>,
+}
+
+impl DecodedEvent {
+    /// Returns the value of the parameter called `name`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| &param.value)
+    }
+}
+
+/// A decoded event parameter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedParam {
+    /// The parameter name, as declared in the ABI.
+    pub name: String,
+    /// The decoded value.
+    pub value: Value,
+    /// Whether the value was read from a topic.
+    pub indexed: bool,
+}
+
+/// A log together with the event decoded from it.
+#[derive(Clone, Debug)]
+pub struct DecodedLog {
+    /// The raw log.
+    pub log: LogEntry,
+    /// The decoded event.
+    pub event: DecodedEvent,
+}
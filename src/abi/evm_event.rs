@@ -0,0 +1,110 @@
+use super::event::{DecodedEvent, Event};
+use crate::errors::DataStreamError;
+use crate::filters::LogFilter;
+use crate::models::LogEntry;
+use crate::types::B256;
+
+/// A Rust struct bound to a Solidity event.
+///
+/// Usually implemented with `#[derive(EvmEvent)]`, which also implements
+/// `TryFrom<&LogEntry>` for the struct.
+///
+/// # Example
+///
+/// ```
+/// use subsquid_data_streaming::{Address, EvmEvent, U256};
+///
+/// #[derive(EvmEvent)]
+/// struct Transfer {
+///     #[indexed]
+///     from: Address,
+///     #[indexed]
+///     to: Address,
+///     value: U256,
+/// }
+///
+/// assert_eq!(Transfer::signature(), "Transfer(address,address,uint256)");
+/// assert_eq!(
+///     Transfer::topic0().to_string(),
+///     "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+/// );
+/// ```
+///
+/// A type given with `#[abi(type = "...")]` is checked at compile time, so a typo does not
+/// compile:
+///
+/// ```compile_fail
+/// use subsquid_data_streaming::{Address, EvmEvent};
+///
+/// #[derive(EvmEvent)]
+/// struct Approval {
+///     #[indexed]
+///     owner: Address,
+///     #[abi(type = "unit256")]
+///     value: subsquid_data_streaming::U256,
+/// }
+/// ```
+///
+/// Tuples cannot be given this way, since their component types would be missing:
+///
+/// ```compile_fail
+/// use subsquid_data_streaming::{Address, EvmEvent};
+///
+/// #[derive(EvmEvent)]
+/// struct Order {
+///     #[abi(type = "tuple")]
+///     maker: Address,
+/// }
+/// ```
+pub trait EvmEvent: Sized {
+    /// Returns the ABI description of the event.
+    fn event() -> &'static Event;
+
+    /// Builds `Self` from an event decoded with [`EvmEvent::event`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if a parameter does not convert into the
+    /// type of its field.
+    fn from_event(event: DecodedEvent) -> Result<Self, DataStreamError>;
+
+    /// Returns the signature hash stored in `topic0` of the event's logs.
+    fn topic0() -> B256 {
+        Self::event().topic0()
+    }
+
+    /// Returns the canonical signature, e.g. `Transfer(address,address,uint256)`.
+    fn signature() -> String {
+        Self::event().signature()
+    }
+
+    /// Returns a `LogFilter` matching logs of this event from any contract.
+    ///
+    /// Add addresses to the returned filter to restrict it to specific contracts.
+    fn log_filter() -> LogFilter {
+        LogFilter::new().add_topic(Self::topic0())
+    }
+
+    /// Returns `true` if `log` carries the signature and number of topics of this event.
+    fn matches(log: &LogEntry) -> bool {
+        let event = Self::event();
+        let signed = event.anonymous || log.topics.first() == Some(&Self::topic0());
+        signed && log.topics.len() == event.topic_count()
+    }
+
+    /// Decodes `log` into `Self`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the log is not an instance of this event
+    /// or its parameters cannot be decoded.
+    fn decode_log(log: &LogEntry) -> Result<Self, DataStreamError> {
+        if !Self::matches(log) {
+            return Err(DataStreamError::DecodingError(format!(
+                "log is not a {} event",
+                Self::signature()
+            )));
+        }
+        Self::from_event(Self::event().decode_log(log)?)
+    }
+}
@@ -0,0 +1,163 @@
+use super::decode::decode;
+use super::param_type::ParamType;
+use super::revert::Revert;
+use super::value::Value;
+use crate::errors::DataStreamError;
+use crate::models::TransactionEntry;
+use crate::types::keccak256;
+
+/// A function declared in a contract ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Function {
+    /// The function name, e.g. `transfer`.
+    pub name: String,
+    /// The function arguments, in declaration order.
+    pub inputs: Vec<Param>,
+    /// The function return values, in declaration order.
+    pub outputs: Vec<Param>,
+}
+
+/// A named parameter of a [`Function`] or a custom error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Param {
+    /// The parameter name. May be empty.
+    pub name: String,
+    /// The parameter type.
+    pub kind: ParamType,
+}
+
+impl Function {
+    /// Returns the canonical signature, e.g. `transfer(address,uint256)`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    /// Returns the 4-byte selector that prefixes the calldata of this function.
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+
+    /// Decodes calldata (including the selector) into named arguments.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the calldata does not start with the
+    /// selector of this function or does not match its argument types.
+    pub fn decode_input(&self, input: &[u8]) -> Result<DecodedCall, DataStreamError> {
+        let selector = self.selector();
+        if input.get(..4) != Some(&selector[..]) {
+            return Err(DataStreamError::DecodingError(format!(
+                "calldata does not start with the selector of {}",
+                self.signature()
+            )));
+        }
+        Ok(DecodedCall {
+            name: self.name.clone(),
+            selector,
+            args: decode_args(&self.inputs, &input[4..])?,
+        })
+    }
+}
+
+/// Calldata decoded with an ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodedInput {
+    /// The selector matched a function of the ABI.
+    Known(DecodedCall),
+    /// The selector matched no function of the ABI.
+    Unknown {
+        /// The first four bytes of the calldata.
+        selector: [u8; 4],
+    },
+}
+
+impl DecodedInput {
+    /// Returns the 4-byte selector of the call.
+    pub fn selector(&self) -> [u8; 4] {
+        match self {
+            DecodedInput::Known(call) => call.selector,
+            DecodedInput::Unknown { selector } => *selector,
+        }
+    }
+
+    /// Returns the decoded call, if the selector matched a function of the ABI.
+    pub fn call(&self) -> Option<&DecodedCall> {
+        match self {
+            DecodedInput::Known(call) => Some(call),
+            DecodedInput::Unknown { .. } => None,
+        }
+    }
+}
+
+/// A function call decoded from calldata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedCall {
+    /// The function name.
+    pub name: String,
+    /// The 4-byte selector of the function.
+    pub selector: [u8; 4],
+    /// The decoded arguments, in declaration order.
+    pub args: Vec<DecodedArg>,
+}
+
+impl DecodedCall {
+    /// Returns the value of the argument called `name`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.args
+            .iter()
+            .find(|arg| arg.name == name)
+            .map(|arg| &arg.value)
+    }
+}
+
+/// A decoded function or error argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedArg {
+    /// The argument name, as declared in the ABI.
+    pub name: String,
+    /// The decoded value.
+    pub value: Value,
+}
+
+/// A transaction together with its decoded calldata and, for failed transactions, the decoded
+/// revert reason.
+#[derive(Clone, Debug)]
+pub struct DecodedTransaction {
+    /// The raw transaction.
+    pub transaction: TransactionEntry,
+    /// The decoded calldata.
+    pub input: DecodedInput,
+    /// The reason the transaction reverted, if its status is 0 and the revert data was found
+    /// in the traces of the same block.
+    pub revert: Option<Revert>,
+}
+
+/// Formats `name(type1,type2,...)`.
+pub(crate) fn signature(name: &str, params: &[Param]) -> String {
+    let types: Vec<String> = params.iter().map(|p| p.kind.to_string()).collect();
+    format!("{}({})", name, types.join(","))
+}
+
+/// Computes the 4-byte selector of a signature.
+pub(crate) fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature);
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&hash.as_bytes()[..4]);
+    selector
+}
+
+/// Decodes ABI-encoded `data` into arguments named after `params`.
+pub(crate) fn decode_args(
+    params: &[Param],
+    data: &[u8],
+) -> Result<Vec<DecodedArg>, DataStreamError> {
+    let types: Vec<ParamType> = params.iter().map(|p| p.kind.clone()).collect();
+    Ok(params
+        .iter()
+        .zip(decode(&types, data)?)
+        .map(|(param, value)| DecodedArg {
+            name: param.name.clone(),
+            value,
+        })
+        .collect())
+}
@@ -0,0 +1,510 @@
+//! Decoding of event logs, transaction calldata and revert data using a contract ABI.
+//!
+//! An [`Abi`] is loaded from the JSON ABI of a contract and used to turn raw [`LogEntry`]
+//! topics and data into named, typed parameters, and transaction input into function calls:
+//!
+//! ```
+//! use subsquid_data_streaming::abi::Abi;
+//!
+//! let abi = Abi::from_json(r#"[{
+//!     "type": "event",
+//!     "name": "Approval",
+//!     "inputs": [
+//!         {"name": "owner", "type": "address", "indexed": true},
+//!         {"name": "spender", "type": "address", "indexed": true},
+//!         {"name": "value", "type": "uint256", "indexed": false}
+//!     ]
+//! }]"#)?;
+//! let approval = abi.event("Approval").unwrap();
+//! assert_eq!(
+//!     approval.topic0().to_string(),
+//!     "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925"
+//! );
+//! # Ok::<(), subsquid_data_streaming::DataStreamError>(())
+//! ```
+//!
+//! [`LogEntry`]: crate::LogEntry
+
+pub mod contract;
+mod decode;
+pub mod event;
+pub mod evm_event;
+pub mod function;
+pub mod param_type;
+pub mod revert;
+pub mod sol_type;
+pub mod value;
+
+pub use contract::Abi;
+pub use event::{DecodedEvent, DecodedLog, DecodedParam, Event, EventParam};
+pub use evm_event::EvmEvent;
+pub use function::{DecodedArg, DecodedCall, DecodedInput, DecodedTransaction, Function, Param};
+pub use param_type::ParamType;
+pub use revert::{AbiError, Revert};
+pub use sol_type::SolType;
+pub use value::Value;
+
+#[cfg(test)]
+#[path = "../../derive/src/type_name.rs"]
+mod derive_type_name;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LogEntry;
+    use crate::types::{Address, Bytes, B256, U256};
+
+    const ERC20_ABI: &str = r#"{"abi": [
+        {"type": "function", "name": "transfer", "inputs": [], "outputs": []},
+        {"type": "event", "name": "Transfer", "anonymous": false, "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "to", "type": "address", "indexed": true},
+            {"name": "value", "type": "uint256", "indexed": false}
+        ]}
+    ]}"#;
+
+    /// Left-pads `value` to a 32-byte word.
+    fn word(value: u64) -> [u8; 32] {
+        U256::from(value).to_be_bytes()
+    }
+
+    /// Builds a log with the given topics and data.
+    fn log(topics: Vec<B256>, data: Vec<u8>) -> LogEntry {
+        serde_json::from_value(serde_json::json!({
+            "topics": topics,
+            "data": Bytes::from(data),
+        }))
+        .unwrap()
+    }
+
+    fn address_topic(address: Address) -> B256 {
+        let mut topic = [0u8; 32];
+        topic[12..].copy_from_slice(address.as_bytes());
+        B256::from(topic)
+    }
+
+    /// Test parsing of nested array and tuple type strings.
+    #[test]
+    fn test_param_type_parse() {
+        let parsed = ParamType::parse("uint8[2][]", &[]).unwrap();
+        assert_eq!(
+            parsed,
+            ParamType::Array(Box::new(ParamType::FixedArray(
+                Box::new(ParamType::Uint(8)),
+                2
+            )))
+        );
+        assert_eq!(parsed.to_string(), "uint8[2][]");
+
+        let tuple = ParamType::parse("tuple[]", &[ParamType::Address, ParamType::Bytes]).unwrap();
+        assert_eq!(tuple.to_string(), "(address,bytes)[]");
+        assert!(tuple.is_dynamic());
+
+        assert_eq!(ParamType::parse("int", &[]).unwrap(), ParamType::Int(256));
+        assert!(ParamType::parse("uint7", &[]).is_err());
+        assert!(ParamType::parse("bytes33", &[]).is_err());
+        assert!(ParamType::parse("mapping", &[]).is_err());
+    }
+
+    /// Test decoding an ERC-20 `Transfer` log.
+    #[test]
+    fn test_decode_transfer() {
+        let abi = Abi::from_json(ERC20_ABI).unwrap();
+        let transfer = abi.event("Transfer").unwrap();
+        assert_eq!(
+            transfer.topic0().to_string(),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+
+        let from = Address::from([0x11; 20]);
+        let to = Address::from([0x22; 20]);
+        let log = log(
+            vec![transfer.topic0(), address_topic(from), address_topic(to)],
+            word(1_000).to_vec(),
+        );
+        let event = abi.decode_log(&log).unwrap().unwrap();
+        assert_eq!(event.name, "Transfer");
+        assert_eq!(event.get("from").unwrap().as_address(), Some(from));
+        assert_eq!(event.get("to").unwrap().as_address(), Some(to));
+        assert_eq!(
+            event.get("value").unwrap().as_uint(),
+            Some(U256::from(1_000u64))
+        );
+        assert!(event.params[0].indexed);
+        assert!(!event.params[2].indexed);
+    }
+
+    /// Test that logs of unknown events are skipped and malformed logs are reported.
+    #[test]
+    fn test_decode_unknown_and_malformed() {
+        let abi = Abi::from_json(ERC20_ABI).unwrap();
+        let unknown = log(vec![B256::from([0xff; 32])], Vec::new());
+        assert!(abi.decode_log(&unknown).unwrap().is_none());
+
+        let topic0 = abi.event("Transfer").unwrap().topic0();
+        let truncated = log(vec![topic0, B256::ZERO, B256::ZERO], vec![0u8; 16]);
+        assert!(matches!(
+            abi.decode_log(&truncated),
+            Err(crate::DataStreamError::DecodingError(_))
+        ));
+    }
+
+    /// Test decoding of dynamic, array, tuple and signed data values and hashed indexed strings.
+    #[test]
+    fn test_decode_dynamic_values() {
+        let abi = Abi::from_json(
+            r#"[{"type": "event", "name": "Complex", "inputs": [
+                {"name": "tag", "type": "string", "indexed": true},
+                {"name": "delta", "type": "int256", "indexed": false},
+                {"name": "memo", "type": "string", "indexed": false},
+                {"name": "ids", "type": "uint256[]", "indexed": false},
+                {"name": "pair", "type": "tuple", "indexed": false, "components": [
+                    {"name": "flag", "type": "bool"},
+                    {"name": "data", "type": "bytes"}
+                ]}
+            ]}]"#,
+        )
+        .unwrap();
+        let event = abi.event("Complex").unwrap();
+        assert_eq!(
+            event.signature(),
+            "Complex(string,int256,string,uint256[],(bool,bytes))"
+        );
+
+        let mut data = Vec::new();
+        data.extend(U256::MAX.to_be_bytes()); // delta = -1
+        data.extend(word(4 * 32)); // memo offset
+        data.extend(word(6 * 32)); // ids offset
+        data.extend(word(9 * 32)); // pair offset
+        data.extend(word(2)); // memo length
+        let mut memo = [0u8; 32];
+        memo[..2].copy_from_slice(b"hi");
+        data.extend(memo);
+        data.extend(word(2)); // ids length
+        data.extend(word(7));
+        data.extend(word(8));
+        data.extend(word(1)); // pair.flag
+        data.extend(word(64)); // pair.data offset, relative to the tuple
+        data.extend(word(3)); // pair.data length
+        let mut bytes = [0u8; 32];
+        bytes[..3].copy_from_slice(&[1, 2, 3]);
+        data.extend(bytes);
+
+        let tag_hash = crate::types::keccak256("tag");
+        let decoded = abi
+            .decode_log(&log(vec![event.topic0(), tag_hash], data))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            decoded.get("tag").unwrap().as_bytes(),
+            Some(&tag_hash.as_bytes()[..])
+        );
+        assert_eq!(decoded.get("delta").unwrap().as_i128(), Some(-1));
+        assert_eq!(decoded.get("delta").unwrap().to_string(), "-1");
+        assert_eq!(decoded.get("memo").unwrap().as_str(), Some("hi"));
+        assert_eq!(decoded.get("ids").unwrap().to_string(), "[7,8]");
+        assert_eq!(
+            decoded.get("pair").unwrap(),
+            &Value::Tuple(vec![
+                Value::Bool(true),
+                Value::Bytes(Bytes::from(vec![1, 2, 3]))
+            ])
+        );
+    }
+
+    /// Test that events sharing a signature are told apart by their number of topics.
+    #[test]
+    fn test_decode_by_topic_count() {
+        let abi = Abi::from_json(
+            r#"[
+                {"type": "event", "name": "Transfer", "inputs": [
+                    {"name": "from", "type": "address", "indexed": true},
+                    {"name": "to", "type": "address", "indexed": true},
+                    {"name": "value", "type": "uint256", "indexed": false}
+                ]},
+                {"type": "event", "name": "Transfer", "inputs": [
+                    {"name": "from", "type": "address", "indexed": true},
+                    {"name": "to", "type": "address", "indexed": true},
+                    {"name": "tokenId", "type": "uint256", "indexed": true}
+                ]}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(abi.topics().len(), 1);
+
+        let topic0 = abi.events[0].topic0();
+        let nft = log(
+            vec![topic0, B256::ZERO, B256::ZERO, B256::from(word(42))],
+            Vec::new(),
+        );
+        let event = abi.decode_log(&nft).unwrap().unwrap();
+        assert_eq!(
+            event.get("tokenId").unwrap().as_uint(),
+            Some(U256::from(42u64))
+        );
+    }
+
+    const TOKEN_ABI: &str = r#"[
+        {"type": "function", "name": "transfer", "stateMutability": "nonpayable",
+         "inputs": [{"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}],
+         "outputs": [{"name": "", "type": "bool"}]},
+        {"type": "error", "name": "InsufficientBalance",
+         "inputs": [{"name": "available", "type": "uint256"}, {"name": "required", "type": "uint256"}]},
+        {"type": "constructor", "inputs": []}
+    ]"#;
+
+    /// Test decoding calldata of a known function and the selector-only fallback.
+    #[test]
+    fn test_decode_input() {
+        let abi = Abi::from_json(TOKEN_ABI).unwrap();
+        let transfer = abi.function("transfer").unwrap();
+        assert_eq!(transfer.selector(), [0xa9, 0x05, 0x9c, 0xbb]);
+
+        let to = Address::from([0x33; 20]);
+        let mut input = transfer.selector().to_vec();
+        input.extend([0u8; 12]);
+        input.extend(to.as_bytes());
+        input.extend(word(5));
+        let decoded = abi.decode_input(&input).unwrap().unwrap();
+        let call = decoded.call().unwrap();
+        assert_eq!(call.name, "transfer");
+        assert_eq!(call.get("to").unwrap().as_address(), Some(to));
+        assert_eq!(
+            call.get("amount").unwrap().as_uint(),
+            Some(U256::from(5u64))
+        );
+
+        let unknown = abi.decode_input(&[1, 2, 3, 4, 5]).unwrap().unwrap();
+        assert_eq!(
+            unknown,
+            DecodedInput::Unknown {
+                selector: [1, 2, 3, 4]
+            }
+        );
+        assert!(abi.decode_input(&[]).unwrap().is_none());
+        assert!(abi.decode_input(&transfer.selector()).is_err());
+    }
+
+    /// Test decoding of `Error(string)`, `Panic(uint256)`, custom and unknown revert data.
+    #[test]
+    fn test_decode_revert() {
+        let abi = Abi::from_json(TOKEN_ABI).unwrap();
+
+        let mut reason = revert::ERROR_SELECTOR.to_vec();
+        reason.extend(word(32));
+        reason.extend(word(4));
+        let mut text = [0u8; 32];
+        text[..4].copy_from_slice(b"nope");
+        reason.extend(text);
+        assert_eq!(
+            abi.decode_revert(&reason).unwrap(),
+            Revert::Reason("nope".to_string())
+        );
+
+        let mut panic = revert::PANIC_SELECTOR.to_vec();
+        panic.extend(word(0x11));
+        let decoded = abi.decode_revert(&panic).unwrap();
+        assert_eq!(decoded, Revert::Panic(U256::from(0x11u64)));
+        assert_eq!(
+            decoded.to_string(),
+            "panic 0x11: arithmetic overflow or underflow"
+        );
+
+        let mut custom = abi.errors[0].selector().to_vec();
+        custom.extend(word(1));
+        custom.extend(word(2));
+        let decoded = abi.decode_revert(&custom).unwrap();
+        assert_eq!(decoded.to_string(), "InsufficientBalance(1,2)");
+
+        assert_eq!(
+            abi.decode_revert(&[0xaa; 6]).unwrap(),
+            Revert::Raw(Bytes::from(vec![0xaa; 6]))
+        );
+        assert_eq!(
+            abi.decode_revert(&[]).unwrap(),
+            Revert::Raw(Bytes::default())
+        );
+    }
+
+    /// Test that the revert reason of a failed transaction is read from its top-level trace.
+    #[test]
+    fn test_decode_transaction_revert() {
+        let abi = Abi::from_json(TOKEN_ABI).unwrap();
+        let mut panic = revert::PANIC_SELECTOR.to_vec();
+        panic.extend(word(1));
+
+        let transaction: crate::TransactionEntry =
+            serde_json::from_value(serde_json::json!({ "transactionIndex": 3, "status": 0 }))
+                .unwrap();
+        let traces: Vec<crate::TraceEntry> = serde_json::from_value(serde_json::json!([
+            { "transactionIndex": 2, "traceAddress": [], "result": { "output": "0x" } },
+            { "transactionIndex": 3, "traceAddress": [0], "result": { "output": "0x" } },
+            { "transactionIndex": 3, "traceAddress": [], "error": "Reverted",
+              "result": { "output": Bytes::from(panic) } }
+        ]))
+        .unwrap();
+
+        let revert = abi
+            .decode_transaction_revert(&transaction, &traces)
+            .unwrap();
+        assert_eq!(revert, Some(Revert::Panic(U256::ONE)));
+
+        let succeeded: crate::TransactionEntry =
+            serde_json::from_value(serde_json::json!({ "transactionIndex": 3, "status": 1 }))
+                .unwrap();
+        assert!(abi
+            .decode_transaction_revert(&succeeded, &traces)
+            .unwrap()
+            .is_none());
+    }
+
+    #[derive(Debug, PartialEq, crate::EvmEvent)]
+    #[abi(name = "Transfer")]
+    struct NftTransfer {
+        #[indexed]
+        from: Address,
+        #[indexed]
+        to: Address,
+        #[indexed]
+        token_id: U256,
+    }
+
+    #[derive(Debug, PartialEq, crate::EvmEvent)]
+    struct Posted {
+        #[indexed]
+        #[abi(type = "string")]
+        tag: B256,
+        amounts: Vec<u64>,
+        delta: i32,
+        pair: (bool, String),
+    }
+
+    /// Test that derived events produce the expected signature and decode matching logs.
+    #[test]
+    fn test_derive_evm_event() {
+        assert_eq!(
+            NftTransfer::signature(),
+            "Transfer(address,address,uint256)"
+        );
+        assert_eq!(
+            NftTransfer::log_filter().topic0,
+            vec![NftTransfer::topic0()]
+        );
+
+        let from = Address::from([0x44; 20]);
+        let nft = log(
+            vec![
+                NftTransfer::topic0(),
+                address_topic(from),
+                B256::ZERO,
+                B256::from(word(7)),
+            ],
+            Vec::new(),
+        );
+        assert_eq!(
+            NftTransfer::try_from(&nft).unwrap(),
+            NftTransfer {
+                from,
+                to: Address::ZERO,
+                token_id: U256::from(7u64),
+            }
+        );
+
+        // An ERC-20 transfer shares topic0 but has one topic less.
+        let erc20 = log(
+            vec![NftTransfer::topic0(), B256::ZERO, B256::ZERO],
+            word(1).to_vec(),
+        );
+        assert!(!NftTransfer::matches(&erc20));
+        assert!(NftTransfer::try_from(&erc20).is_err());
+    }
+
+    /// Test that derived events map Rust types to Solidity types, including overrides.
+    #[test]
+    fn test_derive_evm_event_types() {
+        assert_eq!(
+            Posted::signature(),
+            "Posted(string,uint64[],int32,(bool,string))"
+        );
+
+        let mut data = Vec::new();
+        data.extend(word(3 * 32)); // amounts offset
+        data.extend(U256::MAX.to_be_bytes()); // delta = -1
+        data.extend(word(6 * 32)); // pair offset
+        data.extend(word(2)); // amounts length
+        data.extend(word(10));
+        data.extend(word(20));
+        data.extend(word(1)); // pair.0
+        data.extend(word(64)); // pair.1 offset, relative to the tuple
+        data.extend(word(2)); // pair.1 length
+        let mut text = [0u8; 32];
+        text[..2].copy_from_slice(b"ok");
+        data.extend(text);
+
+        let tag = crate::types::keccak256("tag");
+        let posted = Posted::try_from(&log(vec![Posted::topic0(), tag], data)).unwrap();
+        assert_eq!(
+            posted,
+            Posted {
+                tag,
+                amounts: vec![10, 20],
+                delta: -1,
+                pair: (true, "ok".to_string()),
+            }
+        );
+    }
+
+    /// Test that `#[abi(type = "...")]` accepts exactly the type names `ParamType::parse` does,
+    /// apart from tuples, which need components.
+    #[test]
+    fn test_derive_type_names_match_param_type() {
+        let mut names: Vec<String> = [
+            "address",
+            "bool",
+            "bytes",
+            "string",
+            "uint",
+            "int",
+            "function",
+            "",
+            "[]",
+            "uint[",
+            "uint]",
+            "uint[x]",
+            "uint[-1]",
+            "uint[][2]",
+            "address[2][]",
+            "bytes[][]",
+            "uint8 ",
+            "Uint8",
+            "uint+8",
+            "uint08",
+            "int256x",
+            "bytes0x1",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+        for size in 0..=300 {
+            for prefix in ["uint", "int", "bytes"] {
+                names.push(format!("{}{}", prefix, size));
+                names.push(format!("{}{}[]", prefix, size));
+                names.push(format!("{}{}[3][]", prefix, size));
+            }
+        }
+
+        for name in &names {
+            assert_eq!(
+                derive_type_name::check_type_name(name).is_ok(),
+                ParamType::parse(name, &[]).is_ok(),
+                "{:?}",
+                name
+            );
+        }
+        for name in ["tuple", "tuple[]", "tuple[2][]"] {
+            assert!(ParamType::parse(name, &[]).is_ok());
+            assert!(derive_type_name::check_type_name(name).is_err());
+        }
+    }
+}
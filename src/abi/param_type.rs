@@ -0,0 +1,145 @@
+use crate::errors::DataStreamError;
+use std::fmt;
+
+/// A Solidity ABI type, as used for event and function parameters.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParamType {
+    /// `address`
+    Address,
+    /// `bool`
+    Bool,
+    /// `intN`, with the bit width.
+    Int(usize),
+    /// `uintN`, with the bit width.
+    Uint(usize),
+    /// `bytesN`, with the byte length.
+    FixedBytes(usize),
+    /// `bytes`
+    Bytes,
+    /// `string`
+    String,
+    /// `T[]`
+    Array(Box<ParamType>),
+    /// `T[k]`
+    FixedArray(Box<ParamType>, usize),
+    /// A tuple (struct) of component types.
+    Tuple(Vec<ParamType>),
+}
+
+impl ParamType {
+    /// Parses a type string such as `uint256`, `address[]` or `bytes32[2]`.
+    ///
+    /// Tuples are written as `tuple`, `tuple[]`, etc. in JSON ABIs, with their component types given
+    /// separately in `components`.
+    pub fn parse(type_name: &str, components: &[ParamType]) -> Result<Self, DataStreamError> {
+        if let Some(inner) = type_name.strip_suffix(']') {
+            let open = inner.rfind('[').ok_or_else(|| invalid_type(type_name))?;
+            let element = Box::new(ParamType::parse(&inner[..open], components)?);
+            let size = &inner[open + 1..];
+            return if size.is_empty() {
+                Ok(ParamType::Array(element))
+            } else {
+                let size = size.parse().map_err(|_| invalid_type(type_name))?;
+                Ok(ParamType::FixedArray(element, size))
+            };
+        }
+
+        let sized = |prefix: &str, default: usize| -> Option<Result<usize, DataStreamError>> {
+            let size = type_name.strip_prefix(prefix)?;
+            if size.is_empty() {
+                return Some(Ok(default));
+            }
+            Some(size.parse().map_err(|_| invalid_type(type_name)))
+        };
+
+        match type_name {
+            "address" => Ok(ParamType::Address),
+            "bool" => Ok(ParamType::Bool),
+            "bytes" => Ok(ParamType::Bytes),
+            "string" => Ok(ParamType::String),
+            "tuple" => Ok(ParamType::Tuple(components.to_vec())),
+            _ => {
+                if let Some(size) = sized("uint", 256) {
+                    let size = size?;
+                    check_width(type_name, size)?;
+                    Ok(ParamType::Uint(size))
+                } else if let Some(size) = sized("int", 256) {
+                    let size = size?;
+                    check_width(type_name, size)?;
+                    Ok(ParamType::Int(size))
+                } else if let Some(Ok(size)) = type_name.strip_prefix("bytes").map(str::parse) {
+                    if (1..=32).contains(&size) {
+                        Ok(ParamType::FixedBytes(size))
+                    } else {
+                        Err(invalid_type(type_name))
+                    }
+                } else {
+                    Err(invalid_type(type_name))
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the encoding of this type is dynamic, i.e. stored behind an offset.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::FixedArray(element, _) => element.is_dynamic(),
+            ParamType::Tuple(components) => components.iter().any(ParamType::is_dynamic),
+            _ => false,
+        }
+    }
+
+    /// Returns the number of bytes this type occupies in the head of an encoded tuple.
+    pub(crate) fn head_size(&self) -> usize {
+        if self.is_dynamic() {
+            return 32;
+        }
+        match self {
+            ParamType::FixedArray(element, size) => element.head_size() * size,
+            ParamType::Tuple(components) => components.iter().map(ParamType::head_size).sum(),
+            _ => 32,
+        }
+    }
+}
+
+impl fmt::Display for ParamType {
+    /// Formats the canonical type name used in event and function signatures.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamType::Address => write!(f, "address"),
+            ParamType::Bool => write!(f, "bool"),
+            ParamType::Int(size) => write!(f, "int{}", size),
+            ParamType::Uint(size) => write!(f, "uint{}", size),
+            ParamType::FixedBytes(size) => write!(f, "bytes{}", size),
+            ParamType::Bytes => write!(f, "bytes"),
+            ParamType::String => write!(f, "string"),
+            ParamType::Array(element) => write!(f, "{}[]", element),
+            ParamType::FixedArray(element, size) => write!(f, "{}[{}]", element, size),
+            ParamType::Tuple(components) => {
+                write!(f, "(")?;
+                for (i, component) in components.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", component)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Builds the error returned for an unsupported or malformed type string.
+fn invalid_type(type_name: &str) -> DataStreamError {
+    DataStreamError::DecodingError(format!("unsupported ABI type {:?}", type_name))
+}
+
+/// Checks that an integer width is a multiple of 8 between 8 and 256.
+fn check_width(type_name: &str, size: usize) -> Result<(), DataStreamError> {
+    if size.is_multiple_of(8) && (8..=256).contains(&size) {
+        Ok(())
+    } else {
+        Err(invalid_type(type_name))
+    }
+}
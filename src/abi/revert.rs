@@ -0,0 +1,138 @@
+use super::function::{decode_args, selector, signature, DecodedArg, Param};
+use crate::errors::DataStreamError;
+use crate::types::{Bytes, U256};
+use std::fmt;
+
+/// The selector of the built-in `Error(string)` revert.
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// The selector of the built-in `Panic(uint256)` revert.
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// A custom error declared in a contract ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiError {
+    /// The error name, e.g. `InsufficientBalance`.
+    pub name: String,
+    /// The error arguments, in declaration order.
+    pub inputs: Vec<Param>,
+}
+
+impl AbiError {
+    /// Returns the canonical signature, e.g. `InsufficientBalance(uint256,uint256)`.
+    pub fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    /// Returns the 4-byte selector that prefixes the revert data of this error.
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+}
+
+/// The decoded reason of a reverted call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Revert {
+    /// A `require(..., "reason")` or `revert("reason")`.
+    Reason(String),
+    /// A Solidity panic, such as an arithmetic overflow or a failed `assert`.
+    Panic(U256),
+    /// A custom error declared in the ABI.
+    Custom {
+        /// The error name.
+        name: String,
+        /// The decoded arguments.
+        args: Vec<DecodedArg>,
+    },
+    /// Revert data that could not be decoded, including empty data from a bare `revert()`.
+    Raw(Bytes),
+}
+
+impl Revert {
+    /// Decodes the built-in `Error(string)` and `Panic(uint256)` reverts, falling back to
+    /// `custom` for other selectors.
+    pub(crate) fn decode(
+        data: &[u8],
+        custom: impl FnOnce([u8; 4]) -> Option<AbiError>,
+    ) -> Result<Revert, DataStreamError> {
+        let Some(selector) = data.get(..4).map(|s| <[u8; 4]>::try_from(s).unwrap()) else {
+            return Ok(Revert::Raw(Bytes::from(data)));
+        };
+        let args = &data[4..];
+        match selector {
+            ERROR_SELECTOR => {
+                let reason = decode_args(&[string_param()], args)?.remove(0).value;
+                Ok(Revert::Reason(
+                    reason.as_str().unwrap_or_default().to_string(),
+                ))
+            }
+            PANIC_SELECTOR => {
+                let code = decode_args(&[uint_param()], args)?.remove(0).value;
+                Ok(Revert::Panic(code.as_uint().unwrap_or_default()))
+            }
+            _ => match custom(selector) {
+                Some(error) => Ok(Revert::Custom {
+                    args: decode_args(&error.inputs, args)?,
+                    name: error.name,
+                }),
+                None => Ok(Revert::Raw(Bytes::from(data))),
+            },
+        }
+    }
+
+    /// Returns a description of a Solidity panic code, e.g. `arithmetic overflow or underflow`.
+    pub fn panic_description(code: U256) -> Option<&'static str> {
+        let description = match code.to_u64()? {
+            0x00 => "generic compiler panic",
+            0x01 => "assertion failed",
+            0x11 => "arithmetic overflow or underflow",
+            0x12 => "division or modulo by zero",
+            0x21 => "invalid enum value",
+            0x22 => "invalid storage byte array encoding",
+            0x31 => "pop on empty array",
+            0x32 => "array index out of bounds",
+            0x41 => "out of memory",
+            0x51 => "call to uninitialized function",
+            _ => return None,
+        };
+        Some(description)
+    }
+}
+
+impl fmt::Display for Revert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Revert::Reason(reason) => write!(f, "reverted: {}", reason),
+            Revert::Panic(code) => match Revert::panic_description(*code) {
+                Some(description) => write!(f, "panic {:#x}: {}", code, description),
+                None => write!(f, "panic {:#x}", code),
+            },
+            Revert::Custom { name, args } => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", arg.value)?;
+                }
+                write!(f, ")")
+            }
+            Revert::Raw(data) if data.is_empty() => write!(f, "reverted without data"),
+            Revert::Raw(data) => write!(f, "reverted with {}", data),
+        }
+    }
+}
+
+fn string_param() -> Param {
+    Param {
+        name: "reason".to_string(),
+        kind: super::ParamType::String,
+    }
+}
+
+fn uint_param() -> Param {
+    Param {
+        name: "code".to_string(),
+        kind: super::ParamType::Uint(256),
+    }
+}
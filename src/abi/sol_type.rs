@@ -0,0 +1,219 @@
+use super::param_type::ParamType;
+use super::value::Value;
+use crate::errors::DataStreamError;
+use crate::types::{Address, Bytes, B256, U256};
+
+/// A Rust type that corresponds to a Solidity ABI type.
+///
+/// Used by `#[derive(EvmEvent)]` to infer event signatures from struct fields and to convert
+/// decoded values back into fields.
+///
+/// | Rust                      | Solidity          |
+/// |---------------------------|-------------------|
+/// | `Address`                 | `address`         |
+/// | `bool`                    | `bool`            |
+/// | `u8` ... `u128`, `U256`   | `uint8` ... `uint256` |
+/// | `i8` ... `i128`           | `int8` ... `int128` |
+/// | `B256`                    | `bytes32`         |
+/// | `Bytes`                   | `bytes`           |
+/// | `String`                  | `string`          |
+/// | `Vec<T>`                  | `T[]`             |
+/// | `[T; N]`                  | `T[N]`            |
+/// | `(A, B, ...)`             | `(A,B,...)`       |
+pub trait SolType: Sized {
+    /// Returns the Solidity type of `Self`.
+    fn param_type() -> ParamType;
+
+    /// Converts a decoded value into `Self`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::DecodingError` if the value has a different type or does not
+    /// fit into `Self`.
+    fn from_value(value: Value) -> Result<Self, DataStreamError>;
+}
+
+/// Builds the error returned when a value does not convert into the requested type.
+fn mismatch(value: &Value, expected: &ParamType) -> DataStreamError {
+    DataStreamError::DecodingError(format!("cannot convert {} to {}", value, expected))
+}
+
+impl SolType for Address {
+    fn param_type() -> ParamType {
+        ParamType::Address
+    }
+
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        value
+            .as_address()
+            .ok_or_else(|| mismatch(&value, &Self::param_type()))
+    }
+}
+
+impl SolType for bool {
+    fn param_type() -> ParamType {
+        ParamType::Bool
+    }
+
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        value
+            .as_bool()
+            .ok_or_else(|| mismatch(&value, &Self::param_type()))
+    }
+}
+
+impl SolType for U256 {
+    fn param_type() -> ParamType {
+        ParamType::Uint(256)
+    }
+
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        value
+            .as_uint()
+            .ok_or_else(|| mismatch(&value, &Self::param_type()))
+    }
+}
+
+macro_rules! impl_sol_uint {
+    ($($ty:ty => $bits:expr),*) => {
+        $(
+            impl SolType for $ty {
+                fn param_type() -> ParamType {
+                    ParamType::Uint($bits)
+                }
+
+                fn from_value(value: Value) -> Result<Self, DataStreamError> {
+                    value
+                        .as_uint()
+                        .and_then(|v| v.to_u128())
+                        .and_then(|v| <$ty>::try_from(v).ok())
+                        .ok_or_else(|| mismatch(&value, &Self::param_type()))
+                }
+            }
+        )*
+    };
+}
+
+impl_sol_uint!(u8 => 8, u16 => 16, u32 => 32, u64 => 64, u128 => 128);
+
+macro_rules! impl_sol_int {
+    ($($ty:ty => $bits:expr),*) => {
+        $(
+            impl SolType for $ty {
+                fn param_type() -> ParamType {
+                    ParamType::Int($bits)
+                }
+
+                fn from_value(value: Value) -> Result<Self, DataStreamError> {
+                    value
+                        .as_i128()
+                        .and_then(|v| <$ty>::try_from(v).ok())
+                        .ok_or_else(|| mismatch(&value, &Self::param_type()))
+                }
+            }
+        )*
+    };
+}
+
+impl_sol_int!(i8 => 8, i16 => 16, i32 => 32, i64 => 64, i128 => 128);
+
+impl SolType for B256 {
+    fn param_type() -> ParamType {
+        ParamType::FixedBytes(32)
+    }
+
+    /// Also accepts the hash stored in the topic of an indexed dynamic parameter.
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        match &value {
+            Value::FixedBytes(bytes) if bytes.len() == 32 => B256::from_slice(bytes),
+            _ => Err(mismatch(&value, &Self::param_type())),
+        }
+    }
+}
+
+impl SolType for Bytes {
+    fn param_type() -> ParamType {
+        ParamType::Bytes
+    }
+
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        match value {
+            Value::Bytes(bytes) => Ok(bytes),
+            value => Err(mismatch(&value, &Self::param_type())),
+        }
+    }
+}
+
+impl SolType for String {
+    fn param_type() -> ParamType {
+        ParamType::String
+    }
+
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        match value {
+            Value::String(string) => Ok(string),
+            value => Err(mismatch(&value, &Self::param_type())),
+        }
+    }
+}
+
+impl<T: SolType> SolType for Vec<T> {
+    fn param_type() -> ParamType {
+        ParamType::Array(Box::new(T::param_type()))
+    }
+
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        match value {
+            Value::Array(values) => values.into_iter().map(T::from_value).collect(),
+            value => Err(mismatch(&value, &Self::param_type())),
+        }
+    }
+}
+
+impl<T: SolType, const N: usize> SolType for [T; N] {
+    fn param_type() -> ParamType {
+        ParamType::FixedArray(Box::new(T::param_type()), N)
+    }
+
+    fn from_value(value: Value) -> Result<Self, DataStreamError> {
+        match value {
+            Value::FixedArray(values) if values.len() == N => {
+                let values = values
+                    .into_iter()
+                    .map(T::from_value)
+                    .collect::<Result<Vec<T>, _>>()?;
+                Ok(values
+                    .try_into()
+                    .unwrap_or_else(|_| unreachable!("length checked above")))
+            }
+            value => Err(mismatch(&value, &Self::param_type())),
+        }
+    }
+}
+
+macro_rules! impl_sol_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: SolType),+> SolType for ($($name,)+) {
+            fn param_type() -> ParamType {
+                ParamType::Tuple(vec![$($name::param_type()),+])
+            }
+
+            fn from_value(value: Value) -> Result<Self, DataStreamError> {
+                match value {
+                    Value::Tuple(values) if values.len() == [$(stringify!($name)),+].len() => {
+                        let mut values = values.into_iter();
+                        Ok(($($name::from_value(values.next().unwrap())?,)+))
+                    }
+                    value => Err(mismatch(&value, &Self::param_type())),
+                }
+            }
+        }
+    };
+}
+
+impl_sol_tuple!(A);
+impl_sol_tuple!(A, B);
+impl_sol_tuple!(A, B, C);
+impl_sol_tuple!(A, B, C, D);
+impl_sol_tuple!(A, B, C, D, E);
+impl_sol_tuple!(A, B, C, D, E, F);
@@ -0,0 +1,136 @@
+use crate::types::{Address, Bytes, U256};
+use std::fmt;
+
+/// A decoded ABI value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// An `address`.
+    Address(Address),
+    /// A `bool`.
+    Bool(bool),
+    /// An `intN`, stored in two's complement and sign-extended to 256 bits.
+    Int(U256),
+    /// A `uintN`.
+    Uint(U256),
+    /// A `bytesN`. Also used for indexed parameters of dynamic types, which are only available
+    /// as the Keccak-256 hash of their encoding.
+    FixedBytes(Vec<u8>),
+    /// A `bytes`.
+    Bytes(Bytes),
+    /// A `string`.
+    String(String),
+    /// A `T[]`.
+    Array(Vec<Value>),
+    /// A `T[k]`.
+    FixedArray(Vec<Value>),
+    /// A tuple (struct).
+    Tuple(Vec<Value>),
+}
+
+impl Value {
+    /// Returns the address, if this is an `address`.
+    pub fn as_address(&self) -> Option<Address> {
+        match self {
+            Value::Address(address) => Some(*address),
+            _ => None,
+        }
+    }
+
+    /// Returns the number, if this is a `uintN`.
+    pub fn as_uint(&self) -> Option<U256> {
+        match self {
+            Value::Uint(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an `i128`, if this is an `intN` that fits.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Value::Int(value) => {
+                if value.bit(255) {
+                    let magnitude = (!*value).wrapping_add(U256::ONE).to_u128()?;
+                    if magnitude <= i128::MAX as u128 + 1 {
+                        Some((magnitude as i128).wrapping_neg())
+                    } else {
+                        None
+                    }
+                } else {
+                    value.to_u128().and_then(|value| i128::try_from(value).ok())
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the flag, if this is a `bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if this is a `string`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw bytes, if this is a `bytes` or `bytesN`.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(value) => Some(value),
+            Value::FixedBytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements, if this is an array or a tuple.
+    pub fn as_slice(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) | Value::FixedArray(values) | Value::Tuple(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value the way it would be written in Solidity, e.g. `-1`, `0xabcd`, `[1,2]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Address(address) => write!(f, "{}", address),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Int(value) if value.bit(255) => {
+                write!(f, "-{}", (!*value).wrapping_add(U256::ONE))
+            }
+            Value::Int(value) | Value::Uint(value) => write!(f, "{}", value),
+            Value::FixedBytes(bytes) => write!(f, "{}", Bytes::from(bytes.as_slice())),
+            Value::Bytes(bytes) => write!(f, "{}", bytes),
+            Value::String(value) => write!(f, "{:?}", value),
+            Value::Array(values) | Value::FixedArray(values) => {
+                write!(f, "[")?;
+                write_list(f, values)?;
+                write!(f, "]")
+            }
+            Value::Tuple(values) => {
+                write!(f, "(")?;
+                write_list(f, values)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Writes comma-separated values.
+fn write_list(f: &mut fmt::Formatter<'_>, values: &[Value]) -> fmt::Result {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(f, ",")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    Ok(())
+}
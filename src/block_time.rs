@@ -0,0 +1,256 @@
+use crate::errors::DataStreamError;
+use crate::fields::BlockFields;
+use crate::router_client::RouterClient;
+use crate::worker_client::WorkerClient;
+use crate::worker_query::QuerySet;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The timestamps of the blocks of one dataset looked up so far.
+type TimestampCache = Arc<Mutex<BTreeMap<u64, u64>>>;
+
+/// Block timestamps already fetched, per dataset URL, shared by every stream of the process.
+static DATASETS: OnceLock<Mutex<HashMap<String, TimestampCache>>> = OnceLock::new();
+
+/// Resolves timestamps to block numbers on one dataset by binary search on block headers.
+///
+/// Block timestamps never decrease along a chain, so the first block at or after a timestamp
+/// can be found with about 25 header lookups on a chain of 20 million blocks. Every timestamp
+/// fetched is cached, and later searches start from the narrowest cached bounds, so resolving
+/// the end of a range right after its start, or the same range again, needs few lookups.
+pub(crate) struct BlockTimes {
+    cache: TimestampCache, // Timestamp of every block looked up so far
+}
+
+impl BlockTimes {
+    /// Returns the resolver of the dataset at `url`, sharing the cache of earlier resolvers.
+    pub(crate) fn for_dataset(url: &str) -> Self {
+        let mut datasets = DATASETS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Self {
+            cache: datasets.entry(url.to_string()).or_default().clone(),
+        }
+    }
+
+    /// Returns the first block up to `height` whose timestamp is at or after `timestamp`, or
+    /// `None` if every block is older.
+    ///
+    /// `fetch` returns the timestamp of a block and is only called for blocks not cached yet.
+    pub(crate) async fn first_block_at_or_after<F, Fut>(
+        &self,
+        timestamp: u64,
+        height: u64,
+        fetch: F,
+    ) -> Result<Option<u64>, DataStreamError>
+    where
+        F: Fn(u64) -> Fut,
+        Fut: Future<Output = Result<u64, DataStreamError>>,
+    {
+        if self.timestamp(height, &fetch).await? < timestamp {
+            return Ok(None);
+        }
+        // The answer lies in `low..=high`: `high` is known to be recent enough.
+        let (mut low, mut high) = self.bounds(timestamp, height);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.timestamp(middle, &fetch).await? >= timestamp {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        Ok(Some(low))
+    }
+
+    /// Returns the last block up to `height` whose timestamp is at or before `timestamp`, or
+    /// `None` if every block is newer.
+    pub(crate) async fn last_block_at_or_before<F, Fut>(
+        &self,
+        timestamp: u64,
+        height: u64,
+        fetch: F,
+    ) -> Result<Option<u64>, DataStreamError>
+    where
+        F: Fn(u64) -> Fut,
+        Fut: Future<Output = Result<u64, DataStreamError>>,
+    {
+        let Some(timestamp) = timestamp.checked_add(1) else {
+            return Ok(Some(height));
+        };
+        match self
+            .first_block_at_or_after(timestamp, height, fetch)
+            .await?
+        {
+            Some(0) => Ok(None),
+            Some(block) => Ok(Some(block - 1)),
+            None => Ok(Some(height)),
+        }
+    }
+
+    /// Returns the timestamp of `block`, from the cache or else from `fetch`.
+    async fn timestamp<F, Fut>(&self, block: u64, fetch: &F) -> Result<u64, DataStreamError>
+    where
+        F: Fn(u64) -> Fut,
+        Fut: Future<Output = Result<u64, DataStreamError>>,
+    {
+        if let Some(&timestamp) = self.lock().get(&block) {
+            return Ok(timestamp);
+        }
+        let timestamp = fetch(block).await?;
+        self.lock().insert(block, timestamp);
+        Ok(timestamp)
+    }
+
+    /// Narrows `0..=height` to the blocks between the closest cached blocks older than
+    /// `timestamp` and at or after it.
+    fn bounds(&self, timestamp: u64, height: u64) -> (u64, u64) {
+        let cache = self.lock();
+        let mut low = 0;
+        let mut high = height;
+        for (&block, &block_timestamp) in cache.range(..=height) {
+            if block_timestamp < timestamp {
+                low = low.max(block + 1);
+            } else {
+                high = high.min(block);
+                break;
+            }
+        }
+        (low.min(high), high)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, u64>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Fetches the timestamp of `block` from the worker serving it.
+///
+/// # Errors
+///
+/// Returns a `DataStreamError::InvalidResponse` if the worker does not return the header of
+/// the block or its timestamp.
+pub(crate) async fn fetch_block_timestamp(
+    router_client: &RouterClient,
+    block: u64,
+) -> Result<u64, DataStreamError> {
+    let query = QuerySet {
+        block_options: Some(BlockFields {
+            timestamp: true,
+            ..Default::default()
+        }),
+        include_all_blocks: true,
+        ..Default::default()
+    };
+    let worker_url = router_client.get_worker_url(block).await?;
+    let items = WorkerClient::new(worker_url)
+        .fetch_data(&query.body(block, Some(block))?)
+        .await?;
+    items
+        .iter()
+        .find(|item| item.header.number == block)
+        .and_then(|item| item.header.timestamp)
+        .ok_or_else(|| {
+            DataStreamError::InvalidResponse(format!(
+                "the worker returned no timestamp for block {}",
+                block
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Test that searches find exact range bounds and reuse cached timestamps.
+    #[tokio::test]
+    async fn test_resolve_timestamps() {
+        // Twelve-second blocks from 1000, with two blocks sharing timestamp 1024.
+        let timestamps: Vec<u64> = (0..100u64)
+            .map(|n| 1000 + 12 * n - if n >= 3 { 12 } else { 0 })
+            .collect();
+        assert_eq!(timestamps[2..4], [1024, 1024]);
+        let fetches = AtomicUsize::new(0);
+        let fetch = |block: u64| {
+            fetches.fetch_add(1, Ordering::Relaxed);
+            let timestamp = timestamps[block as usize];
+            async move { Ok(timestamp) }
+        };
+        let times = BlockTimes {
+            cache: Default::default(),
+        };
+
+        assert_eq!(
+            times
+                .first_block_at_or_after(1024, 99, fetch)
+                .await
+                .unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            times
+                .first_block_at_or_after(1025, 99, fetch)
+                .await
+                .unwrap(),
+            Some(4)
+        );
+        assert_eq!(
+            times
+                .last_block_at_or_before(1024, 99, fetch)
+                .await
+                .unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            times
+                .last_block_at_or_before(1035, 99, fetch)
+                .await
+                .unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            times.first_block_at_or_after(0, 99, fetch).await.unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            times.last_block_at_or_before(999, 99, fetch).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            times
+                .first_block_at_or_after(5000, 99, fetch)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            times
+                .last_block_at_or_before(5000, 99, fetch)
+                .await
+                .unwrap(),
+            Some(99)
+        );
+
+        let fetched = fetches.load(Ordering::Relaxed);
+        assert_eq!(
+            times
+                .first_block_at_or_after(1025, 99, fetch)
+                .await
+                .unwrap(),
+            Some(4)
+        );
+        assert_eq!(
+            times
+                .last_block_at_or_before(1024, 99, fetch)
+                .await
+                .unwrap(),
+            Some(3)
+        );
+        assert_eq!(fetches.load(Ordering::Relaxed), fetched);
+    }
+}
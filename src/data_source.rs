@@ -1,8 +1,39 @@
+use serde::{Deserialize, Deserializer};
+
 /// Where data should be fetched from
 ///
 /// The Subsquid data lake currently has an offset of about 1000-2000 blocks from the Ethereum chain tip.
 /// The EVM RPC endpoint can be used to get the "hot blocks" not yet present in the data lake (unimplemented).
+///
+/// When deserialized, the variant is given as a `subsquid` or `evm_rpc` key holding the URL.
+#[derive(Clone, Debug)]
 pub enum DataSource {
     Subsquid(String),
     EvmRpc(String),
 }
+
+impl<'de> Deserialize<'de> for DataSource {
+    /// Deserializes from a map with a single key, which works the same in TOML, YAML and JSON.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Source {
+            subsquid: Option<String>,
+            evm_rpc: Option<String>,
+        }
+
+        match Source::deserialize(deserializer)? {
+            Source {
+                subsquid: Some(url),
+                evm_rpc: None,
+            } => Ok(DataSource::Subsquid(url)),
+            Source {
+                subsquid: None,
+                evm_rpc: Some(url),
+            } => Ok(DataSource::EvmRpc(url)),
+            _ => Err(serde::de::Error::custom(
+                "expected exactly one of `subsquid` or `evm_rpc`",
+            )),
+        }
+    }
+}
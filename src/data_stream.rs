@@ -1,21 +1,31 @@
+use crate::abi::{Abi, DecodedLog, DecodedTransaction, EvmEvent};
+use crate::block_time::{fetch_block_timestamp, BlockTimes};
 use crate::data_source::DataSource;
 use crate::errors::DataStreamError;
-use crate::fields::{LogFields, TransactionFields};
-use crate::filters::{LogFilter, TransactionFilter};
+use crate::factory::Factory;
+use crate::fields::{BlockFields, LogFields, StateDiffFields, TraceFields, TransactionFields};
+use crate::filter_handle::{FilterHandle, Schedule};
+use crate::filters::{LogFilter, StateDiffFilter, TraceFilter, TransactionFilter};
 use crate::models::data_item::{last_block_number, DataItem};
+use crate::models::{BlockContext, LogEntry, TransactionEntry, WithBlock};
+use crate::named_query::NamedQuery;
+use crate::predicates::{FilterStats, Predicates};
+use crate::query_plan::{has_filters, merge_batches, plan_queries, split_query, tag_batch};
 use crate::router_client::RouterClient;
-use crate::utils::parse_block_range;
+use crate::spec::StreamSpec;
+use crate::utils::{parse_block_range, parse_block_range_reverse};
 use crate::worker_client::WorkerClient;
-use crate::worker_query::WorkerQuery;
-use futures::Stream;
+use crate::worker_query::QuerySet;
+use futures::{Stream, StreamExt};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
-use tokio::sync::mpsc::{channel, Receiver};
-use tokio::sync::Semaphore;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 /// `DataStream` represents the main structure for fetching on-chain data from the EVM API.
-/// It streams continuous data batches that match user-defined filters for logs and transactions.
+/// It streams continuous data batches that match user-defined filters for logs, transactions, traces
+/// and state diffs.
 ///
 /// # Usage Example
 /// ```
@@ -24,58 +34,199 @@ use tokio::sync::Semaphore;
 /// let data_stream = DataStream::new()
 ///     .set_data_source(DataSource::Subsquid("https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string()))
 ///     .from_block(6_000_000)
-///     .add_log_filter(LogFilter::new().with_address("0xabcd").with_topic("Burn(address,int24,int24,uint128,uint256)"))
+///     .add_log_filter(
+///         LogFilter::new()
+///             .with_address("0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640")?
+///             .with_topic("Burn(address,int24,int24,uint128,uint256)")?,
+///     )
 ///     .select_log_fields(LogFields::default())
 ///     .select_tx_fields(TransactionFields::default());
 ///
 /// // Stream and process the data
+/// # Ok::<(), subsquid_data_streaming::DataStreamError>(())
 /// ```
 pub struct DataStream {
     data_source: Option<DataSource>, // Specifies the data source (e.g., Subsquid API)
-    log_filters: Vec<LogFilter>,     // Filters for logs to be streamed
-    tx_filters: Vec<TransactionFilter>, // Filters for transactions to be streamed
-    log_options: Option<LogFields>,  // Options for log data (e.g., fields to select)
-    tx_options: Option<TransactionFields>, // Options for transaction data (e.g., fields to select)
+    query: QuerySet, // Filters and field selections for logs, transactions, traces and state diffs
+    predicates: Predicates, // Client-side filters applied inside the fetch tasks
     router_client: Option<RouterClient>, // Router client for interacting with the data source API
     receiver: Option<Receiver<Result<Vec<DataItem>, DataStreamError>>>, // Receiver for streaming data batches
-    current_block: u64,    // Current block number being processed
-    dataset_height: u64,   // Maximum block height available in the dataset
-    from_block: u64,       // Starting block for the data stream
-    to_block: Option<u64>, // Optional end block for the data stream
+    current_block: u64,                     // Current block number being processed
+    dataset_height: u64,                    // Maximum block height available in the dataset
+    from_block: u64,                        // Starting block for the data stream
+    to_block: Option<u64>,                  // Optional end block for the data stream
+    from_time: Option<u64>, // Start timestamp, resolved to `from_block` when building
+    to_time: Option<u64>,   // End timestamp, resolved to `to_block` when building
+    chunk_size: u64,        // Number of blocks per worker query range
+    max_concurrent_tasks: usize, // Maximum number of ranges fetched at the same time
+    max_addresses_per_query: usize, // Maximum number of filter addresses sent in one query
+    ordered: bool,          // Whether batches are delivered in block order
+    reverse: bool,          // Whether blocks are delivered newest first
+    limit: Option<usize>,   // Number of items after which the stream ends
+    delivered: usize,       // Number of items delivered so far
+    named_queries: Vec<QuerySet>, // Named queries fetched alongside the stream's own filters
+    factory: Option<Factory>, // Factory whose children's logs are fetched along with the query
+    schedule: Option<Arc<Mutex<Schedule>>>, // Ranges left to fetch and their current filters, once built
 }
 
+/// The default number of blocks per worker query range.
+pub const DEFAULT_CHUNK_SIZE: u64 = 10_000;
+
+/// The default maximum number of ranges fetched at the same time.
+pub const DEFAULT_MAX_CONCURRENT_TASKS: usize = 20;
+
+/// The default maximum number of filter addresses sent in one worker query.
+pub const DEFAULT_MAX_ADDRESSES_PER_QUERY: usize = 5_000;
+
 impl DataStream {
     /// Creates a new `DataStream` with no data source or filters initially configured.
     pub fn new() -> Self {
         Self {
             data_source: None,
-            log_filters: Vec::new(),
-            tx_filters: Vec::new(),
-            log_options: None,
-            tx_options: None,
+            query: QuerySet::default(),
+            predicates: Predicates::default(),
             router_client: None,
             receiver: None,
             current_block: 0,
             dataset_height: 0,
             from_block: 0,
             to_block: None,
+            from_time: None,
+            to_time: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            max_concurrent_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
+            max_addresses_per_query: DEFAULT_MAX_ADDRESSES_PER_QUERY,
+            ordered: false,
+            reverse: false,
+            limit: None,
+            delivered: 0,
+            named_queries: Vec::new(),
+            factory: None,
+            schedule: None,
         }
     }
 
+    /// Creates a `DataStream` from a [`StreamSpec`], typically loaded from a config file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` naming the offending key if the spec is
+    /// inconsistent, e.g. if `range.to` is below `range.from`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::spec::StreamSpec;
+    /// use subsquid_data_streaming::DataStream;
+    ///
+    /// let spec = StreamSpec::from_yaml_str(
+    ///     r#"
+    /// source:
+    ///   subsquid: https://v2.archive.subsquid.io/network/ethereum-mainnet
+    /// range:
+    ///   from: 20000000
+    ///   to: 20000100
+    /// logs:
+    ///   - address: ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]
+    ///     topic0: ["Transfer(address,address,uint256)"]
+    /// fields:
+    ///   log: { topic0: true, data: true }
+    /// "#,
+    /// )?;
+    /// let data_stream = DataStream::from_spec(spec)?;
+    /// # Ok::<(), subsquid_data_streaming::DataStreamError>(())
+    /// ```
+    pub fn from_spec(spec: StreamSpec) -> Result<Self, DataStreamError> {
+        spec.validate()?;
+
+        let mut stream = DataStream::new()
+            .set_data_source(spec.source)
+            .from_block(spec.range.from)
+            .include_all_blocks(spec.range.include_all_blocks);
+        if let Some(to) = spec.range.to {
+            stream = stream.to_block(to);
+        }
+        stream.query.log_filters = spec.logs;
+        stream.query.tx_filters = spec.transactions;
+        stream.query.trace_filters = spec.traces;
+        stream.query.state_diff_filters = spec.state_diffs;
+        stream.query.block_options = spec.fields.block;
+        stream.query.log_options = spec.fields.log;
+        stream.query.tx_options = spec.fields.transaction;
+        stream.query.trace_options = spec.fields.trace;
+        stream.query.state_diff_options = spec.fields.state_diff;
+        if let Some(chunk_size) = spec.tuning.chunk_size {
+            stream = stream.chunk_size(chunk_size);
+        }
+        if let Some(max_concurrency) = spec.tuning.max_concurrency {
+            stream = stream.max_concurrency(max_concurrency);
+        }
+        if let Some(max_addresses) = spec.tuning.max_addresses_per_query {
+            stream = stream.max_addresses_per_query(max_addresses);
+        }
+        Ok(stream)
+    }
+
     /// Builds the data stream and initializes the router client. This fetches the dataset height and
     /// starts streaming data from the desired block range.
     ///
+    /// Log and transaction filters are normalized first: duplicate values are removed,
+    /// compatible filters are merged and filters subsumed by others are dropped, which never
+    /// changes the matched items. A warning is logged for each filter without conditions, since
+    /// it matches every log or transaction on the chain.
+    ///
     /// # Errors
     /// Returns a `DataStreamError` if there are issues with setting up the stream, such as the data source not being set.
     pub async fn build(mut self) -> Result<Self, DataStreamError> {
+        if self.chunk_size == 0 {
+            return Err(DataStreamError::ConfigurationError(
+                "chunk size must be at least 1".into(),
+            ));
+        }
+        if self.max_concurrent_tasks == 0 {
+            return Err(DataStreamError::ConfigurationError(
+                "max concurrency must be at least 1".into(),
+            ));
+        }
+        if self.max_addresses_per_query == 0 {
+            return Err(DataStreamError::ConfigurationError(
+                "max addresses per query must be at least 1".into(),
+            ));
+        }
+        let mut names = std::collections::HashSet::new();
+        for query in &self.named_queries {
+            let name = query.name.as_deref().unwrap_or_default();
+            if name.is_empty() {
+                return Err(DataStreamError::ConfigurationError(
+                    "named query has an empty name".into(),
+                ));
+            }
+            if !names.insert(name) {
+                return Err(DataStreamError::ConfigurationError(format!(
+                    "named query {} was added twice",
+                    name
+                )));
+            }
+        }
+        if self.factory.is_some() && self.reverse {
+            return Err(DataStreamError::ConfigurationError(
+                "factory tracking needs blocks in ascending order and cannot run in reverse".into(),
+            ));
+        }
+        if self.factory.is_some() && self.query.raw_body.is_some() {
+            return Err(DataStreamError::ConfigurationError(
+                "a raw query cannot be combined with factory tracking".into(),
+            ));
+        }
+        // Reject a malformed raw query before any task is spawned.
+        self.query.body(self.from_block, self.to_block)?;
         match &self.data_source {
             Some(DataSource::Subsquid(url)) => {
                 self.router_client = Some(RouterClient::new(url.clone()));
-                self.dataset_height = self
-                    .router_client
-                    .as_ref()
-                    .unwrap()
-                    .get_dataset_height()
+                let router_client = self.router_client.as_ref().unwrap();
+                self.dataset_height = router_client.get_dataset_height().await?;
+                (self.from_block, self.to_block) = self
+                    .resolve_block_range(url, router_client, self.dataset_height)
                     .await?;
                 if self.current_block == 0 {
                     self.current_block = self.initial_block();
@@ -92,6 +243,51 @@ impl DataStream {
         }
     }
 
+    /// Resolves the timestamps set with [`DataStream::from_time`] and [`DataStream::to_time`]
+    /// to blocks of the dataset at `url`, up to `height`, and returns the range to stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` if no block of the dataset is recent
+    /// enough for the start time or old enough for the end time, or the error of a failed
+    /// header lookup.
+    async fn resolve_block_range(
+        &self,
+        url: &str,
+        router_client: &RouterClient,
+        height: u64,
+    ) -> Result<(u64, Option<u64>), DataStreamError> {
+        let (mut from_block, mut to_block) = self.compute_block_range();
+        let times = BlockTimes::for_dataset(url);
+        let fetch = |block| fetch_block_timestamp(router_client, block);
+        if let Some(from_time) = self.from_time {
+            from_block = times
+                .first_block_at_or_after(from_time, height, fetch)
+                .await?
+                .ok_or_else(|| {
+                    DataStreamError::ConfigurationError(format!(
+                        "no block at or after timestamp {}, the dataset ends at block {}",
+                        from_time, height
+                    ))
+                })?;
+            log::info!("Resolved timestamp {} to block {}", from_time, from_block);
+        }
+        if let Some(to_time) = self.to_time {
+            let block = times
+                .last_block_at_or_before(to_time, height, fetch)
+                .await?
+                .ok_or_else(|| {
+                    DataStreamError::ConfigurationError(format!(
+                        "no block at or before timestamp {}",
+                        to_time
+                    ))
+                })?;
+            log::info!("Resolved timestamp {} to block {}", to_time, block);
+            to_block = Some(block);
+        }
+        Ok((from_block, to_block))
+    }
+
     /// Sets the initial block number to start fetching from.
     fn initial_block(&self) -> u64 {
         self.from_block
@@ -100,6 +296,11 @@ impl DataStream {
     /// Starts the streaming process by submitting block ranges to the worker nodes. It spawns tasks
     /// for each block range and handles the concurrent streaming of data using a semaphore to limit concurrency.
     ///
+    /// Ranges are scheduled by a background task, one at a time as a permit becomes free, with
+    /// the filters current at that moment (see [`FilterHandle`]). If the filters hold more
+    /// addresses than fit in one query, every range is fetched by several sub-queries at once
+    /// and their results are merged before the range is sent.
+    ///
     /// # Errors
     /// Returns a `DataStreamError` if there are issues with worker queries or sending data to the stream.
     async fn start_streaming(&mut self) -> Result<(), DataStreamError> {
@@ -109,75 +310,91 @@ impl DataStream {
         let (from_block, to_block) = self.compute_block_range();
         let max_block = self.dataset_height;
 
-        let chunk_size = 10_000; // Defines the block range size per query
-        let block_ranges = parse_block_range(from_block, to_block, chunk_size, max_block);
-
-        let max_concurrent_tasks = 20; // Limits the number of concurrent block range queries
-        let semaphore = Arc::new(Semaphore::new(max_concurrent_tasks));
-
-        for (start, end) in block_ranges {
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
-
-            let router_client = self.router_client.clone().unwrap();
-            let sender = sender.clone();
-            let log_filters = self.log_filters.clone();
-            let tx_filters = self.tx_filters.clone();
-            let log_options = self.log_options.clone();
-            let tx_options = self.tx_options.clone();
-
-            tokio::spawn(async move {
-                let _permit = permit;
-                let mut current_block = start;
-                let dataset_height = end;
-
-                while current_block <= dataset_height {
-                    match router_client.get_worker_url(current_block).await {
-                        Ok(worker_url) => {
-                            let worker_client = WorkerClient::new(worker_url);
-                            let query = WorkerQuery::from_filters(
-                                current_block,
-                                Some(dataset_height),
-                                &log_filters,
-                                &tx_filters,
-                                &log_options,
-                                &tx_options,
-                            );
-
-                            match worker_client.fetch_data(&query).await {
-                                Ok(data_batch) => {
-                                    let last_block_opt = last_block_number(&data_batch);
-
-                                    if sender.send(Ok(data_batch)).await.is_err() {
-                                        break;
-                                    }
-
-                                    // Move to the next block after the last one processed
-                                    if let Some(last_block) = last_block_opt {
-                                        current_block = last_block + 1;
-                                    } else {
-                                        current_block += 1;
-                                    }
-                                }
-                                Err(e) => {
-                                    if sender.send(Err(e)).await.is_err() {
-                                        break;
-                                    }
-                                    break;
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            let _ = sender.send(Err(e)).await;
+        let block_ranges = self.block_ranges(from_block, to_block, max_block);
+        let schedule = Arc::new(Mutex::new(Schedule::new(
+            self.query.clone(),
+            self.named_queries.clone(),
+            block_ranges,
+            self.reverse,
+            self.max_addresses_per_query,
+        )));
+        self.schedule = Some(schedule.clone());
+
+        // A factory's children are only known once the previous ranges were fetched.
+        let concurrency = if self.factory.is_some() {
+            1
+        } else {
+            self.max_concurrent_tasks
+        };
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        let fetcher = RangeFetcher {
+            router_client: self.router_client.clone().unwrap(),
+            predicates: self.predicates.clone(),
+            sender,
+            factory: self.factory.clone(),
+            factory_fields: self.factory_fields(),
+            max_addresses_per_query: self.max_addresses_per_query,
+        };
+
+        // In ordered mode every range gets its own channel, drained in range order.
+        let ordered_ranges = if self.ordered || self.reverse {
+            let (ranges_sender, ranges) = channel::<Receiver<RangeResult>>(concurrency);
+            tokio::spawn(forward_ranges(ranges, fetcher.sender.clone(), self.reverse));
+            Some(ranges_sender)
+        } else {
+            None
+        };
+
+        tokio::spawn(async move {
+            loop {
+                let permit = semaphore.clone().acquire_owned().await.unwrap();
+                if fetcher.sender.is_closed() {
+                    break;
+                }
+                let next_range = schedule
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .next_range();
+                let Some((start, end, query_sets)) = next_range else {
+                    break;
+                };
+
+                match &ordered_ranges {
+                    Some(ranges) => {
+                        let (range_sender, range_receiver) = channel(10);
+                        if ranges.send(range_receiver).await.is_err() {
                             break;
                         }
+                        let fetcher = RangeFetcher {
+                            sender: range_sender,
+                            ..fetcher.clone()
+                        };
+                        tokio::spawn(fetcher.run(query_sets, start, end, permit));
+                    }
+                    None => {
+                        tokio::spawn(fetcher.clone().run(query_sets, start, end, permit));
                     }
                 }
-            });
-        }
+            }
+        });
 
         Ok(())
     }
 
+    /// Returns a handle to add or remove filter addresses and topics while the stream runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` if the stream has not been built yet.
+    pub fn filter_handle(&self) -> Result<FilterHandle, DataStreamError> {
+        match &self.schedule {
+            Some(schedule) => Ok(FilterHandle::new(schedule.clone())),
+            None => Err(DataStreamError::ConfigurationError(
+                "the stream must be built before its filters can be changed".into(),
+            )),
+        }
+    }
+
     /// Sets the data source for the stream (e.g., Subsquid).
     pub fn set_data_source(mut self, data_source: DataSource) -> Self {
         self.data_source = Some(data_source);
@@ -187,45 +404,685 @@ impl DataStream {
     /// Specifies the starting block for the data stream.
     pub fn from_block(mut self, block_number: u64) -> Self {
         self.from_block = block_number;
+        self.from_time = None;
         self
     }
 
     /// Specifies the ending block for the data stream.
     pub fn to_block(mut self, block_number: u64) -> Self {
         self.to_block = Some(block_number);
+        self.to_time = None;
+        self
+    }
+
+    /// Starts the stream at the first block whose timestamp, in Unix seconds, is at or after
+    /// `timestamp`.
+    ///
+    /// The block is found by [`DataStream::build`] with a binary search on the block headers
+    /// of the archive. Header lookups are cached per dataset for the whole process. This
+    /// replaces any block set with [`DataStream::from_block`], and the other way around.
+    ///
+    /// # Example
+    ///
+    /// Stream every block of March 2024 (UTC):
+    ///
+    /// ```
+    /// use subsquid_data_streaming::{DataSource, DataStream};
+    ///
+    /// let data_stream = DataStream::new()
+    ///     .set_data_source(DataSource::Subsquid(
+    ///         "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
+    ///     ))
+    ///     .from_time(1_709_251_200) // 2024-03-01T00:00:00Z
+    ///     .to_time(1_711_929_599); // 2024-03-31T23:59:59Z
+    /// ```
+    pub fn from_time(mut self, timestamp: u64) -> Self {
+        self.from_time = Some(timestamp);
+        self
+    }
+
+    /// Ends the stream at the last block whose timestamp, in Unix seconds, is at or before
+    /// `timestamp`.
+    ///
+    /// The block is resolved like [`DataStream::from_time`]. This replaces any block set with
+    /// [`DataStream::to_block`], and the other way around. If no block lies between the start
+    /// and end times, the stream is empty.
+    pub fn to_time(mut self, timestamp: u64) -> Self {
+        self.to_time = Some(timestamp);
+        self
+    }
+
+    /// Replaces the query body built from the filters and field selections with `body`.
+    ///
+    /// This gives access to archive features the library does not model yet. The library still
+    /// splits the range, routes each range to a worker and sets `fromBlock` and `toBlock`, so any
+    /// values given for those keys are overwritten. The body must be a JSON object, which is
+    /// checked by [`DataStream::build`]. Filters and field selections added to the stream are
+    /// ignored, but [`DataStream::patch_query`] still applies.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use subsquid_data_streaming::DataStream;
+    ///
+    /// let data_stream = DataStream::new().raw_query(json!({
+    ///     "type": "evm",
+    ///     "logs": [{ "address": ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"] }],
+    ///     "fields": { "log": { "data": true } }
+    /// }));
+    /// ```
+    pub fn raw_query(mut self, body: serde_json::Value) -> Self {
+        self.query.raw_body = Some(body);
+        self
+    }
+
+    /// Edits the JSON body of every worker query before it is sent.
+    ///
+    /// Patches run in the order they were added, on the body built from the filters and
+    /// fields (or on the raw query, if one was set). `fromBlock` and `toBlock` are set after
+    /// the patches run.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::DataStream;
+    ///
+    /// let data_stream = DataStream::new().patch_query(|body| {
+    ///     body["fields"]["log"]["logIndex"] = true.into();
+    /// });
+    /// ```
+    pub fn patch_query<F>(mut self, patch: F) -> Self
+    where
+        F: Fn(&mut serde_json::Value) + Send + Sync + 'static,
+    {
+        self.query.patches.push(Arc::new(patch));
+        self
+    }
+
+    /// Returns the JSON body of the first query of every range, without fetching any data.
+    ///
+    /// Each body is also logged at info level. Later queries of a range, sent when a worker
+    /// returns only part of the range, differ only in `fromBlock`. If the filters are split
+    /// into several queries (see [`DataStream::max_addresses_per_query`]), the body of each
+    /// sub-query is returned, range by range. If no end block is set, the dataset height is
+    /// fetched from the router to compute the ranges, and time bounds are resolved to blocks as
+    /// by [`DataStream::build`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError` if the configuration is invalid or the dataset height cannot
+    /// be fetched.
+    pub async fn dry_run(&self) -> Result<Vec<String>, DataStreamError> {
+        if self.chunk_size == 0 {
+            return Err(DataStreamError::ConfigurationError(
+                "chunk size must be at least 1".into(),
+            ));
+        }
+        let (mut from_block, mut to_block) = self.compute_block_range();
+        let max_block =
+            match to_block {
+                Some(to_block) if self.from_time.is_none() && self.to_time.is_none() => to_block,
+                _ => match &self.data_source {
+                    Some(DataSource::Subsquid(url)) => {
+                        let router_client = RouterClient::new(url.clone());
+                        let height = router_client.get_dataset_height().await?;
+                        (from_block, to_block) = self
+                            .resolve_block_range(url, &router_client, height)
+                            .await?;
+                        height
+                    }
+                    _ => return Err(DataStreamError::ConfigurationError(
+                        "a Subsquid data source is required for a dry run without an end block \
+                         or with time bounds"
+                            .into(),
+                    )),
+                },
+            };
+
+        let query_sets = plan_queries(
+            &self.query,
+            &self.named_queries,
+            self.max_addresses_per_query.max(1),
+        );
+        self.block_ranges(from_block, to_block, max_block)
+            .into_iter()
+            .flat_map(|range| query_sets.iter().map(move |query_set| (range, query_set)))
+            .map(|((start, end), query_set)| {
+                let body = serde_json::to_string_pretty(&query_set.body(start, Some(end))?)?;
+                log::info!("Query for blocks {} to {}: {}", start, end, body);
+                Ok(body)
+            })
+            .collect()
+    }
+
+    /// Sets the number of blocks requested per worker query range (default 10,000).
+    ///
+    /// Smaller chunks spread dense queries over more workers. Larger chunks reduce the number
+    /// of requests for sparse queries.
+    pub fn chunk_size(mut self, blocks: u64) -> Self {
+        self.chunk_size = blocks;
+        self
+    }
+
+    /// Sets the maximum number of ranges fetched at the same time (default 20).
+    pub fn max_concurrency(mut self, tasks: usize) -> Self {
+        self.max_concurrent_tasks = tasks;
+        self
+    }
+
+    /// Sets the maximum number of filter addresses sent in one worker query (default 5,000).
+    ///
+    /// Longer address lists are split across several queries per range, which run at the same
+    /// time and are merged into one ordered, deduplicated batch per range. Splitting buffers a
+    /// whole range before it is delivered, so consider a smaller chunk size for dense queries.
+    pub fn max_addresses_per_query(mut self, addresses: usize) -> Self {
+        self.max_addresses_per_query = addresses;
+        self
+    }
+
+    /// Requests every block in the range, not only blocks containing matching items.
+    ///
+    /// Blocks without matches are returned with their header and no logs, transactions, traces or
+    /// state diffs, which is useful for per-block time series.
+    pub fn include_all_blocks(mut self, include: bool) -> Self {
+        self.query.include_all_blocks = include;
         self
     }
 
     /// Adds a filter for logs to be fetched in the data stream.
     pub fn add_log_filter(mut self, filter: LogFilter) -> Self {
-        self.log_filters.push(filter);
+        self.query.log_filters.push(filter);
+        self
+    }
+
+    /// Adds a filter for logs of the event `T` from any contract.
+    ///
+    /// Also selects the `topic0` and `data` log fields needed to decode the logs with
+    /// [`DataStream::events`]. To restrict the event to specific contracts, add
+    /// `T::log_filter()` with addresses through [`DataStream::add_log_filter`] instead.
+    pub fn add_event<T: EvmEvent>(mut self) -> Self {
+        self.query.log_filters.push(T::log_filter());
+        let options = self
+            .query
+            .log_options
+            .get_or_insert_with(LogFields::default);
+        options.topic0 = true;
+        options.data = true;
+        self
+    }
+
+    /// Delivers batches in block order instead of as soon as they are fetched.
+    ///
+    /// Ranges are still fetched concurrently, but a range is only delivered once every earlier
+    /// range has been, so a slow range holds back the ones after it. At most one range per
+    /// concurrent task is buffered. Every block below the last one delivered is then known to
+    /// be complete, which makes the last delivered block a safe checkpoint.
+    pub fn ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
         self
     }
 
+    /// Walks the block range backward, delivering the newest blocks first.
+    ///
+    /// The range ends at the end block, or at the dataset height if none is set, and ranges
+    /// are scheduled from there down to the start block. Ranges are still fetched
+    /// concurrently, but delivered in order, and the blocks of every batch are in descending
+    /// order. Items within a block keep their ascending order. Every range is held in memory
+    /// until it is complete, so a range costs as much memory as all its matching blocks: lower
+    /// the [chunk size](DataStream::chunk_size) for dense queries. Combined with
+    /// [`DataStream::limit`], this returns the most recent matching items without guessing a
+    /// start block. Factory tracking cannot run in reverse.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::StreamExt;
+    /// use subsquid_data_streaming::{DataSource, DataStream, LogFilter};
+    ///
+    /// # async fn run() -> Result<(), subsquid_data_streaming::DataStreamError> {
+    /// // The 100 most recent USDC transfers.
+    /// let mut data_stream = DataStream::new()
+    ///     .set_data_source(DataSource::Subsquid(
+    ///         "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
+    ///     ))
+    ///     .add_log_filter(
+    ///         LogFilter::new()
+    ///             .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")?
+    ///             .with_topic("Transfer(address,address,uint256)")?,
+    ///     )
+    ///     .reverse(true)
+    ///     .limit(100)
+    ///     .build()
+    ///     .await?;
+    ///
+    /// while let Some(batch) = data_stream.next().await {
+    ///     for block in batch? {
+    ///         println!("block {}: {:?} logs", block.header.number, block.logs.map(|l| l.len()));
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Ends the stream once `items` logs, transactions, traces and state diffs have been
+    /// delivered, counted together.
+    ///
+    /// Blocks are never split, so the block that reaches the limit is delivered whole and may
+    /// take the count past it. Fetching stops once the limit is reached, and a limit of zero
+    /// ends the stream before any batch. Without [`DataStream::ordered`] or
+    /// [`DataStream::reverse`], batches arrive as they are fetched, so the items delivered are
+    /// not necessarily the first ones of the range.
+    pub fn limit(mut self, items: usize) -> Self {
+        self.limit = Some(items);
+        self
+    }
+
+    /// Selects the block timestamp in addition to the current block field selection.
+    pub(crate) fn require_block_timestamp(&mut self) {
+        self.query
+            .block_options
+            .get_or_insert_with(BlockFields::default)
+            .timestamp = true;
+    }
+
+    /// Returns the data source of the stream, if set.
+    pub(crate) fn data_source(&self) -> Option<&DataSource> {
+        self.data_source.as_ref()
+    }
+
+    /// Adds a named query, fetched over the same ranges as the stream's own filters.
+    ///
+    /// Every item records in its `queries` field the names of the queries that returned it.
+    /// See [`NamedQuery`] for how items matched by several queries are merged. With named
+    /// queries, the stream's own filters are optional.
+    pub fn add_query(mut self, query: NamedQuery) -> Self {
+        self.named_queries.push(query.query);
+        self
+    }
+
+    /// Tracks the children created by `factory` and fetches their logs along with the rest of
+    /// the query.
+    ///
+    /// Ranges are then fetched one at a time, in order, since the children of a range are only
+    /// known once the factory events of the earlier ranges were seen. Each range fetches the
+    /// factory events first and then the logs of every child known so far, so logs a child
+    /// emits in the block that created it are included. The factory events are delivered as
+    /// well. Both are fetched with the stream's own block and log field selection, with topics,
+    /// data and address always selected so that they can be decoded.
+    pub fn track_factory(mut self, factory: Factory) -> Self {
+        self.factory = Some(factory);
+        self
+    }
+
+    /// Returns the field selection of the factory and child sub-queries: the stream's own,
+    /// plus the log fields needed to decode factory events. Named queries, a raw query and
+    /// patches do not apply to these sub-queries.
+    fn factory_fields(&self) -> QuerySet {
+        let mut log_options = self.query.log_options.clone().unwrap_or_default();
+        log_options.topic0 = true;
+        log_options.data = true;
+        log_options.address = true;
+        QuerySet {
+            block_options: self.query.block_options.clone(),
+            log_options: Some(log_options),
+            ..Default::default()
+        }
+    }
+
     /// Adds a filter for transactions to be fetched in the data stream.
     pub fn add_tx_filter(mut self, filter: TransactionFilter) -> Self {
-        self.tx_filters.push(filter);
+        self.query.tx_filters.push(filter);
+        self
+    }
+
+    /// Adds a filter for traces (internal calls, creations, etc.) to be fetched in the data stream.
+    pub fn add_trace_filter(mut self, filter: TraceFilter) -> Self {
+        self.query.trace_filters.push(filter);
+        self
+    }
+
+    /// Adds a filter for state diffs (balance, nonce, code and storage changes) to be fetched in the data stream.
+    pub fn add_state_diff_filter(mut self, filter: StateDiffFilter) -> Self {
+        self.query.state_diff_filters.push(filter);
+        self
+    }
+
+    /// Keeps only the logs for which `predicate` returns `true`.
+    ///
+    /// Use this for conditions the archive cannot express, such as thresholds on decoded log
+    /// data. The predicate runs inside the fetch tasks, so dropped logs never take up space in
+    /// the stream's buffer. Blocks left without any item are dropped too, unless
+    /// `include_all_blocks` is set. Calling this several times keeps the logs that satisfy
+    /// every predicate. The number of dropped logs is reported by [`DataStream::filter_stats`].
+    pub fn filter_logs<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&LogEntry) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.logs.push(Arc::new(predicate));
+        self
+    }
+
+    /// Keeps only the transactions for which `predicate` returns `true`.
+    ///
+    /// Behaves like [`DataStream::filter_logs`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::{DataStream, U256};
+    ///
+    /// let hundred_ether = U256::parse_units("100", 18)?;
+    /// let data_stream = DataStream::new()
+    ///     .filter_transactions(move |tx| tx.value.unwrap_or_default() > hundred_ether)
+    ///     .filter_transactions(|tx| tx.input.as_ref().is_some_and(|input| input.len() > 4));
+    /// let stats = data_stream.filter_stats();
+    /// assert_eq!(stats.dropped_transactions(), 0);
+    /// # Ok::<(), subsquid_data_streaming::DataStreamError>(())
+    /// ```
+    pub fn filter_transactions<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&TransactionEntry) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.transactions.push(Arc::new(predicate));
+        self
+    }
+
+    /// Returns a handle reporting how many items the client-side filters have dropped.
+    ///
+    /// The handle stays valid after the stream is consumed or turned into another stream.
+    pub fn filter_stats(&self) -> FilterStats {
+        self.predicates.stats.clone()
+    }
+
+    /// Sets the options for block header data (e.g., timestamp, hash, gas used).
+    pub fn select_block_fields(mut self, options: BlockFields) -> Self {
+        self.query.block_options = Some(options);
         self
     }
 
     /// Sets the options for log data (e.g., fields to include in the results).
     pub fn select_log_fields(mut self, options: LogFields) -> Self {
-        self.log_options = Some(options);
+        self.query.log_options = Some(options);
         self
     }
 
     /// Sets the options for transaction data (e.g., fields to include in the results).
     pub fn select_tx_fields(mut self, options: TransactionFields) -> Self {
-        self.tx_options = Some(options);
+        self.query.tx_options = Some(options);
+        self
+    }
+
+    /// Sets the options for trace data (e.g., fields to include in the results).
+    pub fn select_trace_fields(mut self, options: TraceFields) -> Self {
+        self.query.trace_options = Some(options);
+        self
+    }
+
+    /// Sets the options for state diff data (e.g., fields to include in the results).
+    pub fn select_state_diff_fields(mut self, options: StateDiffFields) -> Self {
+        self.query.state_diff_options = Some(options);
         self
     }
 
+    /// Turns the stream into a stream of logs decoded with `abi`.
+    ///
+    /// Logs whose `topic0` matches no event of the ABI are skipped. A log that matches an event
+    /// but cannot be decoded yields a `DataStreamError::DecodingError`, as do batch errors of the
+    /// underlying stream. Select at least the `topic0` and `data` log fields so that logs can be
+    /// decoded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::StreamExt;
+    /// use subsquid_data_streaming::abi::Abi;
+    /// use subsquid_data_streaming::{DataSource, DataStream, LogFields, LogFilter};
+    ///
+    /// # async fn run(abi_json: &str) -> Result<(), subsquid_data_streaming::DataStreamError> {
+    /// let abi = Abi::from_json(abi_json)?;
+    /// let mut filter = LogFilter::new();
+    /// for topic in abi.topics() {
+    ///     filter = filter.add_topic(topic);
+    /// }
+    /// let mut events = DataStream::new()
+    ///     .set_data_source(DataSource::Subsquid(
+    ///         "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
+    ///     ))
+    ///     .from_block(18_000_000)
+    ///     .to_block(18_000_100)
+    ///     .add_log_filter(filter)
+    ///     .select_log_fields(LogFields {
+    ///         topic0: true,
+    ///         data: true,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()
+    ///     .await?
+    ///     .decode_events(abi);
+    /// while let Some(decoded) = events.next().await {
+    ///     let decoded = decoded?;
+    ///     println!("{} {:?}", decoded.event.name, decoded.event.params);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decode_events(
+        self,
+        abi: Abi,
+    ) -> impl Stream<Item = Result<DecodedLog, DataStreamError>> {
+        self.flat_map_items(move |item| {
+            item.logs
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|log| match abi.decode_log(&log) {
+                    Ok(Some(event)) => Some(Ok(DecodedLog { log, event })),
+                    Ok(None) => None,
+                    Err(e) => Some(Err(e)),
+                })
+                .collect()
+        })
+    }
+
+    /// Turns the stream into a stream of typed events.
+    ///
+    /// Logs that are not instances of `T` are skipped, so several events can be streamed with
+    /// one query and split with `events` on separate streams.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::StreamExt;
+    /// use subsquid_data_streaming::{Address, DataSource, DataStream, EvmEvent, U256};
+    ///
+    /// #[derive(Debug, EvmEvent)]
+    /// struct Transfer {
+    ///     #[indexed]
+    ///     from: Address,
+    ///     #[indexed]
+    ///     to: Address,
+    ///     value: U256,
+    /// }
+    ///
+    /// # async fn run() -> Result<(), subsquid_data_streaming::DataStreamError> {
+    /// let mut transfers = DataStream::new()
+    ///     .set_data_source(DataSource::Subsquid(
+    ///         "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
+    ///     ))
+    ///     .from_block(18_000_000)
+    ///     .to_block(18_000_100)
+    ///     .add_event::<Transfer>()
+    ///     .build()
+    ///     .await?
+    ///     .events::<Transfer>();
+    /// while let Some(transfer) = transfers.next().await {
+    ///     println!("{:?}", transfer?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn events<T: EvmEvent>(self) -> impl Stream<Item = Result<T, DataStreamError>> {
+        self.flat_map_items(|item| {
+            item.logs
+                .unwrap_or_default()
+                .iter()
+                .filter(|log| T::matches(log))
+                .map(T::decode_log)
+                .collect()
+        })
+    }
+
+    /// Turns the stream into a stream of transactions whose calldata is decoded with `abi`.
+    ///
+    /// Calls to functions missing from the ABI are reported with their selector only, and
+    /// transactions without a selector (such as plain ether transfers) are skipped. Failed
+    /// transactions also carry their decoded revert reason when the traces of the block
+    /// include the top-level call of the transaction (see [`Abi::decode_transaction_revert`]).
+    /// Select at least the `input` transaction field, plus `status` and `transaction_index`
+    /// for revert reasons.
+    pub fn decode_transactions(
+        self,
+        abi: Abi,
+    ) -> impl Stream<Item = Result<DecodedTransaction, DataStreamError>> {
+        self.flat_map_items(move |item| {
+            let traces = item.traces.unwrap_or_default();
+            item.transactions
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|transaction| {
+                    let input = match abi.decode_transaction(&transaction) {
+                        Ok(Some(input)) => input,
+                        Ok(None) => return None,
+                        Err(e) => return Some(Err(e)),
+                    };
+                    Some(
+                        abi.decode_transaction_revert(&transaction, &traces)
+                            .map(|revert| DecodedTransaction {
+                                transaction,
+                                input,
+                                revert,
+                            }),
+                    )
+                })
+                .collect()
+        })
+    }
+
+    /// Turns the stream into a stream of single blocks.
+    ///
+    /// Batch errors of the underlying stream are yielded in place of the blocks of that batch.
+    pub fn blocks(self) -> impl Stream<Item = Result<DataItem, DataStreamError>> {
+        self.flat_map_items(|item| vec![Ok(item)])
+    }
+
+    /// Turns the stream into a stream of single logs, each carrying the block it belongs to.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures::StreamExt;
+    /// use subsquid_data_streaming::{DataSource, DataStream, LogFields, LogFilter};
+    ///
+    /// # async fn run() -> Result<(), subsquid_data_streaming::DataStreamError> {
+    /// let mut logs = DataStream::new()
+    ///     .set_data_source(DataSource::Subsquid(
+    ///         "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
+    ///     ))
+    ///     .from_block(18_000_000)
+    ///     .to_block(18_000_100)
+    ///     .add_log_filter(LogFilter::new().with_address("0xdac17f958d2ee523a2206206994597c13d831ec7")?)
+    ///     .select_log_fields(LogFields {
+    ///         log_index: true,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()
+    ///     .await?
+    ///     .logs();
+    /// while let Some(log) = logs.next().await {
+    ///     let log = log?;
+    ///     println!("block {} log {}", log.block.number, log.log_index);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn logs(self) -> impl Stream<Item = Result<WithBlock<LogEntry>, DataStreamError>> {
+        self.flat_map_items(|item| {
+            let block = BlockContext::from(&item.header);
+            item.logs
+                .unwrap_or_default()
+                .into_iter()
+                .map(|log| Ok(WithBlock { block, item: log }))
+                .collect()
+        })
+    }
+
+    /// Turns the stream into a stream of single transactions, each carrying the block it
+    /// belongs to.
+    pub fn transactions(
+        self,
+    ) -> impl Stream<Item = Result<WithBlock<TransactionEntry>, DataStreamError>> {
+        self.flat_map_items(|item| {
+            let block = BlockContext::from(&item.header);
+            item.transactions
+                .unwrap_or_default()
+                .into_iter()
+                .map(|transaction| {
+                    Ok(WithBlock {
+                        block,
+                        item: transaction,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Flattens batches by mapping every block of a batch to zero or more items.
+    ///
+    /// A batch error is passed through as a single error item.
+    fn flat_map_items<T, F>(self, mut f: F) -> impl Stream<Item = Result<T, DataStreamError>>
+    where
+        F: FnMut(DataItem) -> Vec<Result<T, DataStreamError>>,
+    {
+        self.flat_map(move |batch| {
+            let items = match batch {
+                Ok(items) => items.into_iter().flat_map(&mut f).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            futures::stream::iter(items)
+        })
+    }
+
+    /// Splits the block range into the chunks fetched by one task each, in the order they are
+    /// scheduled.
+    fn block_ranges(
+        &self,
+        from_block: u64,
+        to_block: Option<u64>,
+        max_block: u64,
+    ) -> Vec<(u64, u64)> {
+        if self.reverse {
+            parse_block_range_reverse(from_block, to_block, self.chunk_size, max_block)
+        } else {
+            parse_block_range(from_block, to_block, self.chunk_size, max_block)
+        }
+    }
+
     /// Computes the block range for streaming.
     fn compute_block_range(&self) -> (u64, Option<u64>) {
         (self.from_block, self.to_block)
     }
 }
 
+impl Default for DataStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Stream for DataStream {
     type Item = Result<Vec<DataItem>, DataStreamError>;
 
@@ -233,9 +1090,28 @@ impl Stream for DataStream {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
 
+        if this.limit == Some(0) {
+            // Dropping the receiver stops the scheduler and the fetch tasks.
+            this.receiver = None;
+        }
         if let Some(receiver) = &mut this.receiver {
             match Pin::new(receiver).poll_recv(cx) {
-                Poll::Ready(Some(item)) => Poll::Ready(Some(item)),
+                Poll::Ready(Some(Ok(mut batch))) => {
+                    if let Some(limit) = this.limit {
+                        let mut kept = 0;
+                        while kept < batch.len() && this.delivered < limit {
+                            this.delivered += item_count(&batch[kept]);
+                            kept += 1;
+                        }
+                        batch.truncate(kept);
+                        if this.delivered >= limit {
+                            // Dropping the receiver stops the scheduler and the fetch tasks.
+                            this.receiver = None;
+                        }
+                    }
+                    Poll::Ready(Some(Ok(batch)))
+                }
+                Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
                 Poll::Ready(None) => Poll::Ready(None),
                 Poll::Pending => Poll::Pending,
             }
@@ -245,10 +1121,285 @@ impl Stream for DataStream {
     }
 }
 
+#[cfg(test)]
+impl DataStream {
+    /// Builds a stream that yields the given batches, without contacting any worker.
+    pub(crate) fn from_batches(batches: Vec<RangeResult>) -> Self {
+        let (sender, receiver) = channel(batches.len().max(1));
+        for batch in batches {
+            sender.try_send(batch).unwrap();
+        }
+        DataStream {
+            receiver: Some(receiver),
+            ..DataStream::new()
+        }
+    }
+}
+
+/// Forwards the batches of every range received on `ranges` to `sender`, range by range.
+///
+/// In `reverse`, the batches of a range are collected and sent as one batch, newest block
+/// first. If the range fails, only the error is sent: the blocks fetched before the failure are
+/// the oldest of the range, and sending them would skip the newer blocks still missing.
+async fn forward_ranges(
+    mut ranges: Receiver<Receiver<RangeResult>>,
+    sender: Sender<RangeResult>,
+    reverse: bool,
+) {
+    while let Some(mut range) = ranges.recv().await {
+        let mut items = Vec::new();
+        while let Some(batch) = range.recv().await {
+            let batch = match batch {
+                Ok(batch) if reverse => {
+                    items.extend(batch);
+                    continue;
+                }
+                Err(e) => {
+                    items.clear();
+                    Err(e)
+                }
+                batch => batch,
+            };
+            if sender.send(batch).await.is_err() {
+                return;
+            }
+        }
+        items.reverse();
+        if !items.is_empty() && sender.send(Ok(items)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Returns the number of logs, transactions, traces and state diffs in `item`.
+fn item_count(item: &DataItem) -> usize {
+    item.logs.as_ref().map_or(0, Vec::len)
+        + item.transactions.as_ref().map_or(0, Vec::len)
+        + item.traces.as_ref().map_or(0, Vec::len)
+        + item.state_diffs.as_ref().map_or(0, Vec::len)
+}
+
+/// A batch of a range, or the error that ended it.
+type RangeResult = Result<Vec<DataItem>, DataStreamError>;
+
+/// The state shared by the tasks fetching the ranges of a stream.
+#[derive(Clone)]
+struct RangeFetcher {
+    router_client: RouterClient,
+    predicates: Predicates,
+    sender: Sender<RangeResult>,
+    factory: Option<Factory>,
+    factory_fields: QuerySet, // Field selection of the factory and child sub-queries
+    max_addresses_per_query: usize,
+}
+
+impl RangeFetcher {
+    /// Fetches one block range and sends its batches, holding `permit` until done.
+    async fn run(
+        self,
+        query_sets: Arc<Vec<QuerySet>>,
+        start: u64,
+        end: u64,
+        permit: OwnedSemaphorePermit,
+    ) {
+        let _permit = permit;
+        let RangeFetcher {
+            router_client,
+            predicates,
+            sender,
+            ..
+        } = &self;
+
+        if let Some(factory) = &self.factory {
+            let include_all_blocks = query_sets.iter().any(|set| set.include_all_blocks);
+            let result = self
+                .fetch_factory_range(factory, &query_sets, start, end)
+                .await
+                .map(|batch| predicates.apply(batch, include_all_blocks));
+            match result {
+                Ok(data_batch) if data_batch.is_empty() => {}
+                result => {
+                    let _ = sender.send(result).await;
+                }
+            }
+            return;
+        }
+
+        if query_sets.len() > 1 || query_sets[0].name.is_some() {
+            let batches = fetch_sets(router_client, &query_sets, start, end).await;
+            let include_all_blocks = query_sets.iter().any(|set| set.include_all_blocks);
+            match batches
+                .map(|batches| predicates.apply(merge_batches(batches), include_all_blocks))
+            {
+                Ok(data_batch) if data_batch.is_empty() => {}
+                result => {
+                    let _ = sender.send(result).await;
+                }
+            }
+            return;
+        }
+
+        let query_set = &query_sets[0];
+        let mut current_block = start;
+        let dataset_height = end;
+
+        while current_block <= dataset_height {
+            match router_client.get_worker_url(current_block).await {
+                Ok(worker_url) => {
+                    let worker_client = WorkerClient::new(worker_url);
+                    let query = match query_set.body(current_block, Some(dataset_height)) {
+                        Ok(query) => query,
+                        Err(e) => {
+                            let _ = sender.send(Err(e)).await;
+                            break;
+                        }
+                    };
+
+                    match worker_client.fetch_data(&query).await {
+                        Ok(data_batch) => {
+                            let last_block_opt = last_block_number(&data_batch);
+                            let data_batch =
+                                predicates.apply(data_batch, query_set.include_all_blocks);
+
+                            if !data_batch.is_empty() && sender.send(Ok(data_batch)).await.is_err()
+                            {
+                                break;
+                            }
+
+                            // Move to the next block after the last one processed
+                            if let Some(last_block) = last_block_opt {
+                                current_block = last_block + 1;
+                            } else {
+                                current_block += 1;
+                            }
+                        }
+                        Err(e) => {
+                            if sender.send(Err(e)).await.is_err() {
+                                break;
+                            }
+                            break;
+                        }
+                    }
+                }
+                Err(e) => {
+                    let _ = sender.send(Err(e)).await;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Fetches a range of a factory-tracking stream.
+    ///
+    /// The factory events of the range are fetched first, so that children created in the
+    /// range are known before their logs are fetched. The stream's own queries and the child
+    /// logs are then fetched at the same time and merged with the factory events.
+    async fn fetch_factory_range(
+        &self,
+        factory: &Factory,
+        query_sets: &[QuerySet],
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<DataItem>, DataStreamError> {
+        let only = |log_filter: LogFilter| QuerySet {
+            log_filters: vec![log_filter],
+            ..self.factory_fields.clone()
+        };
+
+        let factory_batch = fetch_range(
+            &self.router_client,
+            &only(factory.log_filter()),
+            start,
+            end,
+            None,
+        )
+        .await?;
+        let found = factory.discover(&factory_batch);
+        if found > 0 {
+            log::debug!(
+                "Found {} new children of the factory in blocks {} to {}",
+                found,
+                start,
+                end
+            );
+        }
+
+        let mut sets: Vec<QuerySet> = query_sets
+            .iter()
+            .filter(|query_set| query_set.include_all_blocks || has_filters(query_set))
+            .cloned()
+            .collect();
+        if let Some(child_filter) = factory.child_filter() {
+            sets.extend(split_query(
+                &only(child_filter),
+                self.max_addresses_per_query,
+            ));
+        }
+
+        let mut batches = vec![factory_batch];
+        batches.extend(fetch_sets(&self.router_client, &sets, start, end).await?);
+        Ok(merge_batches(batches))
+    }
+}
+
+/// Fetches a whole block range with several query sets at once, sharing the lookup of the
+/// worker serving the start of the range. The items of named query sets are tagged with the
+/// name of their set.
+async fn fetch_sets(
+    router_client: &RouterClient,
+    query_sets: &[QuerySet],
+    start: u64,
+    end: u64,
+) -> Result<Vec<Vec<DataItem>>, DataStreamError> {
+    let worker_url = router_client.get_worker_url(start).await?;
+    futures::future::try_join_all(query_sets.iter().map(|query_set| {
+        let worker_url = worker_url.clone();
+        async move {
+            let mut batch =
+                fetch_range(router_client, query_set, start, end, Some(worker_url)).await?;
+            if let Some(name) = &query_set.name {
+                tag_batch(&mut batch, name);
+            }
+            Ok::<_, DataStreamError>(batch)
+        }
+    }))
+    .await
+}
+
+/// Fetches a whole block range with one query set, following the worker's partial responses
+/// until the end of the range.
+///
+/// `first_worker` is the worker to send the first query to, if it was already looked up.
+async fn fetch_range(
+    router_client: &RouterClient,
+    query_set: &QuerySet,
+    start: u64,
+    end: u64,
+    mut first_worker: Option<String>,
+) -> Result<Vec<DataItem>, DataStreamError> {
+    let mut items = Vec::new();
+    let mut current_block = start;
+    while current_block <= end {
+        let worker_url = match first_worker.take() {
+            Some(worker_url) => worker_url,
+            None => router_client.get_worker_url(current_block).await?,
+        };
+        let query = query_set.body(current_block, Some(end))?;
+        let data_batch = WorkerClient::new(worker_url).fetch_data(&query).await?;
+
+        // Move to the next block after the last one processed
+        current_block = last_block_number(&data_batch).unwrap_or(current_block) + 1;
+        items.extend(data_batch);
+    }
+    Ok(items)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::filters::LogFilter;
+    use crate::types::{keccak256, Address};
+    use crate::worker_query::WorkerQuery;
 
     /// Test the creation of a `DataStream` with a data source.
     #[tokio::test]
@@ -268,13 +1419,285 @@ mod tests {
             ))
             .from_block(6_082_465)
             .add_log_filter(LogFilter {
-                address: vec!["0xabcd".to_string()],
+                address: vec![Address::from([0xab; 20])],
                 topic0: vec![
-                    "Burn(address,int24,int24,uint128,uint256)".to_string(),
-                    "Initialize(uint160,int24)".to_string(),
+                    keccak256("Burn(address,int24,int24,uint128,uint256)"),
+                    keccak256("Initialize(uint160,int24)"),
                 ],
             });
         assert!(data_stream.data_source.is_some());
-        assert_eq!(data_stream.log_filters.first().unwrap().topic0.len(), 2);
+        assert_eq!(
+            data_stream.query.log_filters.first().unwrap().topic0.len(),
+            2
+        );
+    }
+
+    /// Test the addition of trace filters to the data stream.
+    #[tokio::test]
+    async fn test_data_stream_add_trace() {
+        let data_stream = DataStream::new()
+            .add_trace_filter(
+                TraceFilter::new()
+                    .with_type(crate::filters::TraceType::Call)
+                    .with_call_to("0x7A250D5630B4CF539739DF2C5DACB4C659F2488D")
+                    .unwrap(),
+            )
+            .select_trace_fields(TraceFields {
+                call_value: true,
+                ..Default::default()
+            });
+        let filter = data_stream.query.trace_filters.first().unwrap();
+        assert_eq!(
+            filter.call_to[0].to_checksum(),
+            "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"
+        );
+        assert!(data_stream.query.trace_options.is_some());
+    }
+
+    /// Test the addition of state diff filters to the data stream.
+    #[tokio::test]
+    async fn test_data_stream_add_state_diff() {
+        let data_stream = DataStream::new()
+            .add_state_diff_filter(
+                StateDiffFilter::new()
+                    .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+                    .unwrap()
+                    .with_key("balance")
+                    .with_kind(crate::filters::StateDiffKind::Changed),
+            )
+            .select_state_diff_fields(StateDiffFields {
+                prev: true,
+                next: true,
+                ..Default::default()
+            });
+        let filter = data_stream.query.state_diff_filters.first().unwrap();
+        assert_eq!(
+            format!("{:x}", filter.address[0]),
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+        assert_eq!(filter.key, vec!["balance".to_string()]);
+        assert!(data_stream.query.state_diff_options.is_some());
+    }
+
+    /// Test that including all blocks is forwarded to the worker query.
+    #[tokio::test]
+    async fn test_data_stream_include_all_blocks() {
+        let data_stream = DataStream::new().include_all_blocks(true);
+        let query = WorkerQuery::from_filters(1, Some(10), &data_stream.query);
+        let json = serde_json::to_value(&query).unwrap();
+        assert_eq!(json["includeAllBlocks"], serde_json::Value::Bool(true));
+
+        let query = WorkerQuery::from_filters(1, Some(10), &DataStream::new().query);
+        let json = serde_json::to_value(&query).unwrap();
+        assert!(json.get("includeAllBlocks").is_none());
+    }
+
+    fn stream_of(batches: Vec<Result<Vec<DataItem>, DataStreamError>>) -> DataStream {
+        DataStream::from_batches(batches)
+    }
+
+    /// Builds a block with two logs and one transaction.
+    fn block(number: u64) -> DataItem {
+        serde_json::from_value(serde_json::json!({
+            "header": { "number": number, "timestamp": 1_700_000_000 + number },
+            "logs": [{ "logIndex": 0 }, { "logIndex": 1 }],
+            "transactions": [{ "transactionIndex": 0 }],
+        }))
+        .unwrap()
+    }
+
+    /// Test that the flattening adapters yield single items with their block context.
+    #[tokio::test]
+    async fn test_flattened_streams() {
+        let logs: Vec<_> = stream_of(vec![Ok(vec![block(1), block(2)]), Ok(vec![block(3)])])
+            .logs()
+            .collect()
+            .await;
+        let logs: Vec<(u64, Option<u64>, u64)> = logs
+            .into_iter()
+            .map(|log| log.unwrap())
+            .map(|log| (log.block.number, log.block.timestamp, log.log_index))
+            .collect();
+        assert_eq!(
+            logs,
+            vec![
+                (1, Some(1_700_000_001), 0),
+                (1, Some(1_700_000_001), 1),
+                (2, Some(1_700_000_002), 0),
+                (2, Some(1_700_000_002), 1),
+                (3, Some(1_700_000_003), 0),
+                (3, Some(1_700_000_003), 1),
+            ]
+        );
+
+        let transactions: Vec<_> = stream_of(vec![Ok(vec![block(1), block(2)])])
+            .transactions()
+            .collect()
+            .await;
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[1].as_ref().unwrap().block.number, 2);
+
+        let blocks: Vec<_> = stream_of(vec![
+            Ok(vec![block(1)]),
+            Err(DataStreamError::InvalidResponse("boom".to_string())),
+            Ok(vec![block(2)]),
+        ])
+        .blocks()
+        .collect()
+        .await;
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].as_ref().unwrap().header.number, 1);
+        assert!(blocks[1].is_err());
+        assert_eq!(blocks[2].as_ref().unwrap().header.number, 2);
+    }
+
+    /// Test that factory sub-queries select what decoding needs, whatever the other queries
+    /// select.
+    #[test]
+    fn test_factory_fields() {
+        let event = crate::abi::Event {
+            name: "PairCreated".to_string(),
+            inputs: vec![crate::abi::EventParam {
+                name: "pair".to_string(),
+                kind: crate::abi::ParamType::Address,
+                indexed: false,
+            }],
+            anonymous: false,
+        };
+        let data_stream = DataStream::new()
+            .track_factory(Factory::new(event, "pair").unwrap())
+            .select_log_fields(LogFields {
+                log_index: true,
+                ..Default::default()
+            })
+            .add_query(NamedQuery::new("blocks").select_log_fields(LogFields::default()))
+            .patch_query(|body| body["logs"] = serde_json::Value::Null);
+
+        let fields = data_stream.factory_fields();
+        let log_options = fields.log_options.as_ref().unwrap();
+        assert!(log_options.topic0 && log_options.data && log_options.address);
+        assert!(log_options.log_index);
+        assert!(fields.raw_body.is_none() && fields.patches.is_empty());
+    }
+
+    /// Test that a limit keeps whole blocks up to the one reaching it, then ends the stream.
+    #[tokio::test]
+    async fn test_limit() {
+        let mut data_stream = stream_of(vec![
+            Ok(vec![block(1), block(2), block(3)]),
+            Ok(vec![block(4)]),
+        ]);
+        data_stream.limit = Some(4);
+        let batches: Vec<_> = data_stream.collect().await;
+        assert_eq!(batches.len(), 1);
+        let numbers: Vec<u64> = batches[0]
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|b| b.header.number)
+            .collect();
+        assert_eq!(numbers, vec![1, 2]);
+    }
+
+    /// Test that empty and repeated named query names are rejected with their own errors.
+    #[tokio::test]
+    async fn test_named_query_names() {
+        let error = |stream: DataStream| async move {
+            match stream.to_block(10).build().await {
+                Err(DataStreamError::ConfigurationError(message)) => message,
+                _ => panic!("expected a configuration error"),
+            }
+        };
+        assert_eq!(
+            error(DataStream::new().add_query(NamedQuery::new(""))).await,
+            "named query has an empty name"
+        );
+        assert_eq!(
+            error(
+                DataStream::new()
+                    .add_query(NamedQuery::new("swaps"))
+                    .add_query(NamedQuery::new("swaps"))
+            )
+            .await,
+            "named query swaps was added twice"
+        );
+    }
+
+    /// Test that a limit of zero ends the stream without any batch.
+    #[tokio::test]
+    async fn test_limit_zero() {
+        let mut data_stream = stream_of(vec![Ok(vec![block(1)])]);
+        data_stream.limit = Some(0);
+        assert!(data_stream.next().await.is_none());
+    }
+
+    /// Test that the block in which the limit falls is delivered whole.
+    #[tokio::test]
+    async fn test_limit_inside_block() {
+        let mut data_stream = stream_of(vec![Ok(vec![block(1), block(2)])]);
+        data_stream.limit = Some(1);
+        let batch = data_stream.next().await.unwrap().unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(item_count(&batch[0]), 3);
+        assert!(data_stream.next().await.is_none());
+    }
+
+    /// Test that in reverse, ranges are sent newest block first, and that a failed range
+    /// sends only its error.
+    #[tokio::test]
+    async fn test_forward_ranges_in_reverse() {
+        let (ranges_sender, ranges) = channel(2);
+        let (sender, mut receiver) = channel(10);
+        for batches in [
+            vec![Ok(vec![block(20), block(21)]), Ok(vec![block(22)])],
+            vec![
+                Ok(vec![block(10)]),
+                Ok(vec![block(11)]),
+                Err(DataStreamError::InvalidResponse("worker failed".into())),
+            ],
+        ] {
+            let (range_sender, range) = channel(3);
+            for batch in batches {
+                range_sender.try_send(batch).unwrap();
+            }
+            ranges_sender.try_send(range).unwrap();
+        }
+        drop(ranges_sender);
+        forward_ranges(ranges, sender, true).await;
+
+        let numbers = |batch: Option<RangeResult>| -> Vec<u64> {
+            batch
+                .unwrap()
+                .unwrap()
+                .iter()
+                .map(|b| b.header.number)
+                .collect()
+        };
+        assert_eq!(numbers(receiver.recv().await), vec![22, 21, 20]);
+        assert!(receiver.recv().await.unwrap().is_err());
+        assert!(receiver.recv().await.is_none());
+    }
+
+    /// Test that reverse mode schedules full ranges from the end block down to the start block.
+    #[tokio::test]
+    async fn test_reverse_ranges() {
+        let bodies = DataStream::new()
+            .to_block(25)
+            .chunk_size(10)
+            .reverse(true)
+            .dry_run()
+            .await
+            .unwrap();
+        let ranges: Vec<(u64, u64)> = bodies
+            .iter()
+            .map(|body| {
+                let body: serde_json::Value = serde_json::from_str(body).unwrap();
+                (
+                    body["fromBlock"].as_u64().unwrap(),
+                    body["toBlock"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(ranges, vec![(16, 25), (6, 15), (0, 5)]);
     }
 }
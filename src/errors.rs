@@ -10,4 +10,8 @@ pub enum DataStreamError {
     InvalidResponse(String),
     #[error("Deserialization error: {0}")]
     DeserializationError(serde_json::Error),
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+    #[error("Decoding error: {0}")]
+    DecodingError(String),
 }
@@ -0,0 +1,282 @@
+use crate::abi::{Event, EvmEvent, ParamType};
+use crate::errors::DataStreamError;
+use crate::filters::LogFilter;
+use crate::models::DataItem;
+use crate::types::{Address, B256};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+/// A factory contract whose children are tracked by a [`DataStream`].
+///
+/// The factory announces each child it creates with an event, such as Uniswap's `PairCreated`
+/// or `PoolCreated`, holding the child address in one of its parameters. A stream tracking the
+/// factory fetches these events along with the logs of every child found so far, including
+/// logs a child emits in the block that created it.
+///
+/// The set of children is shared between clones of a `Factory`, so keep a clone to read it
+/// back, e.g. to persist it and resume later with [`Factory::with_children`].
+///
+/// [`DataStream`]: crate::DataStream
+///
+/// # Example
+///
+/// ```
+/// use subsquid_data_streaming::{Address, DataStream, EvmEvent, Factory};
+///
+/// #[derive(EvmEvent)]
+/// struct PairCreated {
+///     #[indexed]
+///     token0: Address,
+///     #[indexed]
+///     token1: Address,
+///     pair: Address,
+///     index: subsquid_data_streaming::U256,
+/// }
+///
+/// let factory = Factory::for_event::<PairCreated>("pair")?
+///     .with_address("0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f".parse()?)
+///     .with_child_topic(subsquid_data_streaming::types::keccak256(
+///         "Swap(address,uint256,uint256,uint256,uint256,address)",
+///     ));
+/// let data_stream = DataStream::new().track_factory(factory.clone());
+/// # Ok::<(), subsquid_data_streaming::DataStreamError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Factory {
+    event: Event,                            // The event announcing a new child
+    parameter: String,                       // The event parameter holding the child address
+    addresses: Vec<Address>,                 // The factory contracts, empty for any emitter
+    child_topics: Vec<B256>,                 // The topic0 values of child logs, empty for all
+    children: Arc<Mutex<BTreeSet<Address>>>, // The children found so far
+}
+
+impl Factory {
+    /// Creates a factory announcing children with `event`, whose `parameter` holds the child
+    /// address.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the event is anonymous, or if it has no
+    /// `address` parameter named `parameter`.
+    pub fn new(event: Event, parameter: &str) -> Result<Self, DataStreamError> {
+        if event.anonymous {
+            return Err(DataStreamError::ValidationError(format!(
+                "factory event {} is anonymous and cannot be filtered by topic",
+                event.name
+            )));
+        }
+        match event.inputs.iter().find(|input| input.name == parameter) {
+            Some(input) if input.kind == ParamType::Address => {}
+            Some(input) => {
+                return Err(DataStreamError::ValidationError(format!(
+                    "parameter {} of {} is a {}, not an address",
+                    parameter, event.name, input.kind
+                )))
+            }
+            None => {
+                return Err(DataStreamError::ValidationError(format!(
+                    "event {} has no parameter named {}",
+                    event.signature(),
+                    parameter
+                )))
+            }
+        }
+        Ok(Self {
+            event,
+            parameter: parameter.to_string(),
+            addresses: Vec::new(),
+            child_topics: Vec::new(),
+            children: Arc::new(Mutex::new(BTreeSet::new())),
+        })
+    }
+
+    /// Creates a factory announcing children with the event bound to `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` as for [`Factory::new`].
+    pub fn for_event<T: EvmEvent>(parameter: &str) -> Result<Self, DataStreamError> {
+        Self::new(T::event().clone(), parameter)
+    }
+
+    /// Only accepts children announced by the factory contract at `address`.
+    ///
+    /// Without any address, children announced by any contract emitting the event are tracked.
+    pub fn with_address(mut self, address: Address) -> Self {
+        self.addresses.push(address);
+        self
+    }
+
+    /// Only fetches child logs whose `topic0` is `topic`. Without any topic, every log of the
+    /// children is fetched.
+    pub fn with_child_topic(mut self, topic: B256) -> Self {
+        self.child_topics.push(topic);
+        self
+    }
+
+    /// Adds children found earlier, e.g. when resuming from a checkpoint.
+    pub fn with_children(self, children: impl IntoIterator<Item = Address>) -> Self {
+        self.lock().extend(children);
+        self
+    }
+
+    /// Returns the children found so far, in address order.
+    pub fn children(&self) -> Vec<Address> {
+        self.lock().iter().copied().collect()
+    }
+
+    /// Returns the filter matching the factory event.
+    pub(crate) fn log_filter(&self) -> LogFilter {
+        LogFilter {
+            address: self.addresses.clone(),
+            topic0: vec![self.event.topic0()],
+        }
+    }
+
+    /// Returns the filter matching the logs of the children found so far, or `None` if there
+    /// are none yet. An empty address list would match every contract.
+    pub(crate) fn child_filter(&self) -> Option<LogFilter> {
+        let children = self.lock();
+        if children.is_empty() {
+            return None;
+        }
+        Some(LogFilter {
+            address: children.iter().copied().collect(),
+            topic0: self.child_topics.clone(),
+        })
+    }
+
+    /// Adds the children announced by the factory events in `batch` and returns how many
+    /// were new.
+    ///
+    /// Logs of the event that do not decode, e.g. another event with the same signature but
+    /// different indexed parameters, are skipped with a warning.
+    pub(crate) fn discover(&self, batch: &[DataItem]) -> usize {
+        let topic0 = self.event.topic0();
+        let mut found = Vec::new();
+        for (block, log) in batch.iter().flat_map(|item| {
+            item.logs
+                .iter()
+                .flatten()
+                .map(|log| (item.header.number, log))
+        }) {
+            // The query already filters on the factory addresses, so a log without its address
+            // selected is trusted to come from one of them.
+            if log.topics.first() != Some(&topic0)
+                || log
+                    .address
+                    .is_some_and(|a| !self.addresses.is_empty() && !self.addresses.contains(&a))
+            {
+                continue;
+            }
+            match self.event.decode_log(log) {
+                Ok(event) => {
+                    if let Some(child) = event.get(&self.parameter).and_then(|v| v.as_address()) {
+                        found.push(child);
+                    }
+                }
+                Err(e) => log::warn!(
+                    "Skipping {} log {} of block {}: {}",
+                    self.event.name,
+                    log.log_index,
+                    block,
+                    e
+                ),
+            }
+        }
+
+        let mut children = self.lock();
+        found
+            .into_iter()
+            .filter(|child| children.insert(*child))
+            .count()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeSet<Address>> {
+        self.children
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi::Abi;
+
+    /// Test that children are decoded from factory logs and only counted once.
+    #[test]
+    fn test_discover_children() {
+        let abi = Abi::from_json(
+            r#"[{
+                "type": "event",
+                "name": "PairCreated",
+                "anonymous": false,
+                "inputs": [
+                    { "name": "token0", "type": "address", "indexed": true },
+                    { "name": "token1", "type": "address", "indexed": true },
+                    { "name": "pair", "type": "address", "indexed": false },
+                    { "name": "", "type": "uint256", "indexed": false }
+                ]
+            }]"#,
+        )
+        .unwrap();
+        let event = abi.event("PairCreated").unwrap().clone();
+        assert!(matches!(
+            Factory::new(event.clone(), "token2"),
+            Err(DataStreamError::ValidationError(_))
+        ));
+
+        let factory_address: Address = "0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f"
+            .parse()
+            .unwrap();
+        let factory = Factory::new(event.clone(), "pair")
+            .unwrap()
+            .with_address(factory_address);
+        assert!(factory.child_filter().is_none());
+
+        let word = |n: u64| format!("{:064x}", n);
+        let block: DataItem = serde_json::from_value(serde_json::json!({
+            "header": { "number": 10 },
+            "logs": [{
+                "topics": [
+                    event.topic0().to_string(),
+                    format!("0x{}", word(0xa)),
+                    format!("0x{}", word(0xb)),
+                ],
+                "data": format!("0x{}{}", word(0xc), word(1)),
+                "transactionIndex": 0,
+                "logIndex": 0,
+                "address": factory_address.to_string().to_lowercase(),
+                "blockNumber": 10,
+                "blockHash": format!("0x{}", word(0)),
+                "transactionHash": format!("0x{}", word(0)),
+                "removed": false
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(factory.discover(std::slice::from_ref(&block)), 1);
+        assert_eq!(factory.discover(std::slice::from_ref(&block)), 0);
+        let child: Address = Address::from_low_u64(0xc);
+        assert_eq!(factory.clone().children(), vec![child]);
+        assert_eq!(factory.child_filter().unwrap().address, vec![child]);
+
+        // A log whose address was not selected is still accepted.
+        let mut json = serde_json::to_value(&block).unwrap();
+        json["logs"][0].as_object_mut().unwrap().remove("address");
+        json["logs"][0]["data"] = format!("0x{}{}", word(0xd), word(2)).into();
+        let block: DataItem = serde_json::from_value(json).unwrap();
+        assert!(block.logs.as_ref().unwrap()[0].address.is_none());
+        assert_eq!(factory.discover(std::slice::from_ref(&block)), 1);
+        assert_eq!(factory.children().len(), 2);
+
+        let other = Factory::new(event, "pair")
+            .unwrap()
+            .with_address(Address::from_low_u64(0xf));
+        let mut json = serde_json::to_value(&block).unwrap();
+        json["logs"][0]["address"] = factory_address.to_string().to_lowercase().into();
+        let block: DataItem = serde_json::from_value(json).unwrap();
+        assert_eq!(other.discover(std::slice::from_ref(&block)), 0);
+    }
+}
@@ -0,0 +1,63 @@
+use super::fields::field_map;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Represents options for selecting block header fields.
+///
+/// The block number is always returned, regardless of the selection.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlockFields {
+    pub number: bool,
+    pub hash: bool,
+    pub parent_hash: bool,
+    pub timestamp: bool,
+    pub transactions_root: bool,
+    pub receipts_root: bool,
+    pub state_root: bool,
+    pub logs_bloom: bool,
+    pub sha3_uncles: bool,
+    pub extra_data: bool,
+    pub miner: bool,
+    pub nonce: bool,
+    pub mix_hash: bool,
+    pub size: bool,
+    pub gas_limit: bool,
+    pub gas_used: bool,
+    pub difficulty: bool,
+    pub total_difficulty: bool,
+    pub base_fee_per_gas: bool,
+    pub blob_gas_used: bool,
+    pub excess_blob_gas: bool,
+    pub l1_block_number: bool,
+}
+
+impl BlockFields {
+    /// Builds the field selection map sent to the worker, keyed by the wire name of each field.
+    pub(crate) fn to_field_map(&self) -> BTreeMap<String, bool> {
+        field_map(&[
+            ("number", self.number),
+            ("hash", self.hash),
+            ("parentHash", self.parent_hash),
+            ("timestamp", self.timestamp),
+            ("transactionsRoot", self.transactions_root),
+            ("receiptsRoot", self.receipts_root),
+            ("stateRoot", self.state_root),
+            ("logsBloom", self.logs_bloom),
+            ("sha3Uncles", self.sha3_uncles),
+            ("extraData", self.extra_data),
+            ("miner", self.miner),
+            ("nonce", self.nonce),
+            ("mixHash", self.mix_hash),
+            ("size", self.size),
+            ("gasLimit", self.gas_limit),
+            ("gasUsed", self.gas_used),
+            ("difficulty", self.difficulty),
+            ("totalDifficulty", self.total_difficulty),
+            ("baseFeePerGas", self.base_fee_per_gas),
+            ("blobGasUsed", self.blob_gas_used),
+            ("excessBlobGas", self.excess_blob_gas),
+            ("l1BlockNumber", self.l1_block_number),
+        ])
+    }
+}
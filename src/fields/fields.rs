@@ -1,17 +1,38 @@
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-/// Represents the fields that can be selected for logs and transactions.
+/// Represents the fields that can be selected for block headers, logs, transactions, traces and state diffs.
 ///
-/// This struct allows for specifying which fields of logs and transactions should be included
+/// This struct allows for specifying which fields of block headers, logs, transactions, traces and state diffs should be included
 /// in the response. The fields are represented as a map where the keys are field names and the
-/// values are booleans indicating whether the field should be included.
+/// values are booleans indicating whether the field should be included. Maps are ordered so that
+/// the serialized query is stable.
 #[derive(Clone, Debug, Serialize)]
 pub struct Fields {
+    /// Optional map specifying which fields of block headers to include.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<BTreeMap<String, bool>>,
     /// Optional map specifying which fields of logs to include.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub log: Option<HashMap<String, bool>>,
+    pub log: Option<BTreeMap<String, bool>>,
     /// Optional map specifying which fields of transactions to include.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub transaction: Option<HashMap<String, bool>>,
+    pub transaction: Option<BTreeMap<String, bool>>,
+    /// Optional map specifying which fields of traces to include.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<BTreeMap<String, bool>>,
+    /// Optional map specifying which fields of state diffs to include.
+    #[serde(rename = "stateDiff", skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<BTreeMap<String, bool>>,
+}
+
+/// Builds a field selection map from `(wire name, selected)` pairs.
+///
+/// Only selected fields are kept, since the worker treats a missing field as not selected.
+pub(crate) fn field_map(fields: &[(&str, bool)]) -> BTreeMap<String, bool> {
+    fields
+        .iter()
+        .filter(|(_, selected)| *selected)
+        .map(|(name, selected)| (name.to_string(), *selected))
+        .collect()
 }
@@ -1,11 +1,13 @@
-use serde::Serialize;
+use super::fields::field_map;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Represents options for selecting log fields.
 ///
-/// This struct contains a map that defines which log fields should be included in the response.
-/// The map's keys are the field names, and the values are booleans indicating whether the field
-/// should be included or excluded.
-#[derive(Clone, Debug, Default, Serialize)]
+/// Each flag defines whether the corresponding log field should be included in the response.
+/// `topic0` selects the whole `topics` array.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LogFields {
     pub topic0: bool,
     pub data: bool,
@@ -17,3 +19,20 @@ pub struct LogFields {
     pub transaction_hash: bool,
     pub removed: bool,
 }
+
+impl LogFields {
+    /// Builds the field selection map sent to the worker, keyed by the wire name of each field.
+    pub(crate) fn to_field_map(&self) -> BTreeMap<String, bool> {
+        field_map(&[
+            ("topics", self.topic0),
+            ("data", self.data),
+            ("transactionIndex", self.transaction_index),
+            ("logIndex", self.log_index),
+            ("address", self.address),
+            ("blockNumber", self.block_number),
+            ("blockHash", self.block_hash),
+            ("transactionHash", self.transaction_hash),
+            ("removed", self.removed),
+        ])
+    }
+}
@@ -1,11 +1,22 @@
-//! Options for specifying what data to return from filtered Ethereum logs and transactions
+//! Options for specifying what data to return from block headers and filtered Ethereum logs, transactions,
+//! traces and state diffs
 
-/// Options for selecting both log and transaction fields.
+/// Options for selecting block header fields.
+pub mod block_fields;
+/// Options for selecting block, log, transaction, trace and state diff fields.
+#[allow(clippy::module_inception)]
 pub mod fields;
 /// Options for selecting log fields.
 pub mod log_fields;
+/// Options for selecting state diff fields.
+pub mod state_diff_fields;
+/// Options for selecting trace fields.
+pub mod trace_fields;
 /// Options for selecting transaction fields.
 pub mod transaction_fields;
 
+pub use block_fields::BlockFields;
 pub use log_fields::LogFields;
+pub use state_diff_fields::StateDiffFields;
+pub use trace_fields::TraceFields;
 pub use transaction_fields::TransactionFields;
@@ -0,0 +1,29 @@
+use super::fields::field_map;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Represents options for selecting state diff fields.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StateDiffFields {
+    pub transaction_index: bool,
+    pub address: bool,
+    pub key: bool,
+    pub kind: bool,
+    pub prev: bool,
+    pub next: bool,
+}
+
+impl StateDiffFields {
+    /// Builds the field selection map sent to the worker, keyed by the wire name of each field.
+    pub(crate) fn to_field_map(&self) -> BTreeMap<String, bool> {
+        field_map(&[
+            ("transactionIndex", self.transaction_index),
+            ("address", self.address),
+            ("key", self.key),
+            ("kind", self.kind),
+            ("prev", self.prev),
+            ("next", self.next),
+        ])
+    }
+}
@@ -0,0 +1,76 @@
+use super::fields::field_map;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Represents options for selecting trace fields.
+///
+/// Each flag corresponds to a field of the trace object returned by the data lake. Fields of the
+/// `create`, `call`, `suicide` and `reward` actions are only populated for traces of that type.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraceFields {
+    pub transaction_index: bool,
+    pub trace_address: bool,
+    pub subtraces: bool,
+    pub error: bool,
+    pub revert_reason: bool,
+    pub trace_type: bool,
+    pub create_from: bool,
+    pub create_value: bool,
+    pub create_gas: bool,
+    pub create_init: bool,
+    pub create_result_gas_used: bool,
+    pub create_result_code: bool,
+    pub create_result_address: bool,
+    pub call_from: bool,
+    pub call_to: bool,
+    pub call_value: bool,
+    pub call_gas: bool,
+    pub call_input: bool,
+    pub call_sighash: bool,
+    pub call_type: bool,
+    pub call_result_gas_used: bool,
+    pub call_result_output: bool,
+    pub suicide_address: bool,
+    pub suicide_refund_address: bool,
+    pub suicide_balance: bool,
+    pub reward_author: bool,
+    pub reward_value: bool,
+    pub reward_type: bool,
+}
+
+impl TraceFields {
+    /// Builds the field selection map sent to the worker, keyed by the wire name of each field.
+    pub(crate) fn to_field_map(&self) -> BTreeMap<String, bool> {
+        field_map(&[
+            ("transactionIndex", self.transaction_index),
+            ("traceAddress", self.trace_address),
+            ("subtraces", self.subtraces),
+            ("error", self.error),
+            ("revertReason", self.revert_reason),
+            ("type", self.trace_type),
+            ("createFrom", self.create_from),
+            ("createValue", self.create_value),
+            ("createGas", self.create_gas),
+            ("createInit", self.create_init),
+            ("createResultGasUsed", self.create_result_gas_used),
+            ("createResultCode", self.create_result_code),
+            ("createResultAddress", self.create_result_address),
+            ("callFrom", self.call_from),
+            ("callTo", self.call_to),
+            ("callValue", self.call_value),
+            ("callGas", self.call_gas),
+            ("callInput", self.call_input),
+            ("callSighash", self.call_sighash),
+            ("callType", self.call_type),
+            ("callResultGasUsed", self.call_result_gas_used),
+            ("callResultOutput", self.call_result_output),
+            ("suicideAddress", self.suicide_address),
+            ("suicideRefundAddress", self.suicide_refund_address),
+            ("suicideBalance", self.suicide_balance),
+            ("rewardAuthor", self.reward_author),
+            ("rewardValue", self.reward_value),
+            ("rewardType", self.reward_type),
+        ])
+    }
+}
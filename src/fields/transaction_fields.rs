@@ -1,11 +1,12 @@
-use serde::Serialize;
+use super::fields::field_map;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Represents options for selecting transaction fields.
 ///
-/// This struct contains a map that defines which transaction fields should be included in the response.
-/// The map's keys are the field names, and the values are booleans indicating whether the field should
-/// be included or excluded.
-#[derive(Clone, Debug, Default, Serialize)]
+/// Each flag defines whether the corresponding transaction field should be included in the response.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TransactionFields {
     pub hash: bool,
     pub nonce: bool,
@@ -16,4 +17,59 @@ pub struct TransactionFields {
     pub gas: bool,
     pub gas_price: bool,
     pub input: bool,
+    pub block_number: bool,
+    pub block_hash: bool,
+    pub max_fee_per_gas: bool,
+    pub max_priority_fee_per_gas: bool,
+    pub tx_type: bool,
+    pub status: bool,
+    pub gas_used: bool,
+    pub cumulative_gas_used: bool,
+    pub effective_gas_price: bool,
+    pub contract_address: bool,
+    pub chain_id: bool,
+    pub v: bool,
+    pub r: bool,
+    pub s: bool,
+    pub y_parity: bool,
+    pub access_list: bool,
+    pub sighash: bool,
+    pub max_fee_per_blob_gas: bool,
+    pub blob_versioned_hashes: bool,
+}
+
+impl TransactionFields {
+    /// Builds the field selection map sent to the worker, keyed by the wire name of each field.
+    pub(crate) fn to_field_map(&self) -> BTreeMap<String, bool> {
+        field_map(&[
+            ("hash", self.hash),
+            ("nonce", self.nonce),
+            ("transactionIndex", self.transaction_index),
+            ("to", self.to),
+            ("from", self.from),
+            ("value", self.value),
+            ("gas", self.gas),
+            ("gasPrice", self.gas_price),
+            ("input", self.input),
+            ("blockNumber", self.block_number),
+            ("blockHash", self.block_hash),
+            ("maxFeePerGas", self.max_fee_per_gas),
+            ("maxPriorityFeePerGas", self.max_priority_fee_per_gas),
+            ("type", self.tx_type),
+            ("status", self.status),
+            ("gasUsed", self.gas_used),
+            ("cumulativeGasUsed", self.cumulative_gas_used),
+            ("effectiveGasPrice", self.effective_gas_price),
+            ("contractAddress", self.contract_address),
+            ("chainId", self.chain_id),
+            ("v", self.v),
+            ("r", self.r),
+            ("s", self.s),
+            ("yParity", self.y_parity),
+            ("accessList", self.access_list),
+            ("sighash", self.sighash),
+            ("maxFeePerBlobGas", self.max_fee_per_blob_gas),
+            ("blobVersionedHashes", self.blob_versioned_hashes),
+        ])
+    }
 }
@@ -0,0 +1,447 @@
+use crate::errors::DataStreamError;
+use crate::filters::{LogFilter, TransactionFilter};
+use crate::query_plan::plan_queries;
+use crate::types::{Address, B256};
+use crate::worker_query::QuerySet;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// The ranges of a running stream that have not been scheduled yet, and the filters they will
+/// be fetched with.
+///
+/// The scheduler takes one range at a time, when a fetch task is free to run it, so filter
+/// updates apply to every range taken after them.
+pub(crate) struct Schedule {
+    query: QuerySet,                     // The filters as last updated through the handle
+    named: Vec<QuerySet>,                // The named queries fetched alongside `query`
+    ranges: VecDeque<(u64, u64)>,        // Ranges not scheduled yet, in order
+    reverse: bool,                       // Whether ranges are walked from the newest block
+    max_addresses_per_query: usize,      // Limit used to split the query of each range
+    planned: Option<Arc<Vec<QuerySet>>>, // Sub-queries planned for `query`, if still valid
+}
+
+impl Schedule {
+    /// Creates a schedule for `ranges`, fetched with `query`. In `reverse`, the ranges walk
+    /// down from the newest block.
+    pub(crate) fn new(
+        query: QuerySet,
+        named: Vec<QuerySet>,
+        ranges: Vec<(u64, u64)>,
+        reverse: bool,
+        max_addresses_per_query: usize,
+    ) -> Self {
+        Self {
+            query,
+            named,
+            ranges: ranges.into(),
+            reverse,
+            max_addresses_per_query,
+            planned: None,
+        }
+    }
+
+    /// Takes the next range and the sub-queries to fetch it with, or `None` once every range
+    /// has been scheduled.
+    pub(crate) fn next_range(&mut self) -> Option<(u64, u64, Arc<Vec<QuerySet>>)> {
+        let (start, end) = self.ranges.pop_front()?;
+        let (query, named) = (&self.query, &self.named);
+        let max_addresses = self.max_addresses_per_query;
+        let planned = self
+            .planned
+            .get_or_insert_with(|| Arc::new(plan_queries(query, named, max_addresses)));
+        Some((start, end, planned.clone()))
+    }
+
+    /// Applies `update` to the filters and returns the first block fetched with them.
+    fn update(&mut self, update: impl FnOnce(&mut QuerySet)) -> Option<u64> {
+        update(&mut self.query);
+        self.planned = None;
+        self.next_block()
+    }
+
+    /// Returns the first block delivered from the next range: its lowest block, or its highest
+    /// in reverse.
+    fn next_block(&self) -> Option<u64> {
+        self.ranges
+            .front()
+            .map(|&(start, end)| if self.reverse { end } else { start })
+    }
+}
+
+/// A handle to change the log and transaction filters of a running [`DataStream`].
+///
+/// Every update returns the block from which it takes effect: the first block delivered from
+/// the first range scheduled after the update. Ranges scheduled earlier, which may still be in
+/// flight, keep the filters they were scheduled with, so items of blocks below that number are
+/// matched by the old filters. In [reverse](crate::DataStream::reverse), the returned block is
+/// the highest of the range, and the new filters apply to it and every block below. `None`
+/// means every range had already been scheduled and the update has no effect.
+///
+/// A filter without addresses or topics matches every log, so removing the last address or
+/// topic of a filter is rejected rather than widening the filter to the whole chain. The same
+/// holds for the `to` and `from` addresses of a transaction filter. Updates do not change a raw
+/// query set with [`DataStream::raw_query`].
+///
+/// Filters are addressed by their index in the order they were added to the stream.
+///
+/// [`DataStream`]: crate::DataStream
+/// [`DataStream::raw_query`]: crate::DataStream::raw_query
+///
+/// # Example
+///
+/// ```no_run
+/// use subsquid_data_streaming::{Address, DataSource, DataStream, LogFilter};
+///
+/// # async fn run(pool: Address) -> Result<(), subsquid_data_streaming::DataStreamError> {
+/// let data_stream = DataStream::new()
+///     .set_data_source(DataSource::Subsquid(
+///         "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
+///     ))
+///     .add_log_filter(LogFilter::new().with_topic("Swap(address,address,int256,int256,uint160,uint128,int24)")?)
+///     .build()
+///     .await?;
+/// let filters = data_stream.filter_handle()?;
+///
+/// // Later, once a new pool is discovered:
+/// if let Some(block) = filters.add_log_addresses(0, [pool])? {
+///     println!("watching {} from block {}", pool, block);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FilterHandle {
+    schedule: Arc<Mutex<Schedule>>,
+}
+
+impl FilterHandle {
+    pub(crate) fn new(schedule: Arc<Mutex<Schedule>>) -> Self {
+        Self { schedule }
+    }
+
+    /// Adds addresses to the log filter at `filter`, skipping those it already holds.
+    ///
+    /// Note that a log filter without addresses matches every address, so adding the first
+    /// address narrows it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no log filter at `filter`.
+    pub fn add_log_addresses(
+        &self,
+        filter: usize,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_log_filter(filter, |f| add_all(&mut f.address, addresses))
+    }
+
+    /// Removes addresses from the log filter at `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no log filter at `filter`, or
+    /// if the update would remove every address of the filter. The filter is then unchanged.
+    pub fn remove_log_addresses(
+        &self,
+        filter: usize,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_log_filter(filter, |f| remove_all(&mut f.address, addresses, "address"))
+    }
+
+    /// Adds topics to the `topic0` list of the log filter at `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no log filter at `filter`.
+    pub fn add_log_topics(
+        &self,
+        filter: usize,
+        topics: impl IntoIterator<Item = B256>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_log_filter(filter, |f| add_all(&mut f.topic0, topics))
+    }
+
+    /// Removes topics from the `topic0` list of the log filter at `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no log filter at `filter`, or
+    /// if the update would remove every topic of the filter. The filter is then unchanged.
+    pub fn remove_log_topics(
+        &self,
+        filter: usize,
+        topics: impl IntoIterator<Item = B256>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_log_filter(filter, |f| remove_all(&mut f.topic0, topics, "topic"))
+    }
+
+    /// Adds addresses to the `to` list of the transaction filter at `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no transaction filter at
+    /// `filter`.
+    pub fn add_tx_to_addresses(
+        &self,
+        filter: usize,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_tx_filter(filter, |f| {
+            add_all(f.to.get_or_insert_with(Vec::new), addresses)
+        })
+    }
+
+    /// Removes addresses from the `to` list of the transaction filter at `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no transaction filter at
+    /// `filter`, or if the update would remove every `to` address of the filter. The filter is
+    /// then unchanged.
+    pub fn remove_tx_to_addresses(
+        &self,
+        filter: usize,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_tx_filter(filter, |f| match &mut f.to {
+            Some(to) => remove_all(to, addresses, "`to` address"),
+            None => Ok(()),
+        })
+    }
+
+    /// Adds addresses to the `from` list of the transaction filter at `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no transaction filter at
+    /// `filter`.
+    pub fn add_tx_from_addresses(
+        &self,
+        filter: usize,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_tx_filter(filter, |f| {
+            add_all(f.from.get_or_insert_with(Vec::new), addresses)
+        })
+    }
+
+    /// Removes addresses from the `from` list of the transaction filter at `filter`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if there is no transaction filter at
+    /// `filter`, or if the update would remove every `from` address of the filter. The filter is
+    /// then unchanged.
+    pub fn remove_tx_from_addresses(
+        &self,
+        filter: usize,
+        addresses: impl IntoIterator<Item = Address>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        self.update_tx_filter(filter, |f| match &mut f.from {
+            Some(from) => remove_all(from, addresses, "`from` address"),
+            None => Ok(()),
+        })
+    }
+
+    /// Adds a log filter and returns its index along with the block it takes effect from.
+    pub fn add_log_filter(&self, filter: LogFilter) -> (usize, Option<u64>) {
+        let mut index = 0;
+        let block = self.lock().update(|query| {
+            index = query.log_filters.len();
+            query.log_filters.push(filter);
+        });
+        (index, block)
+    }
+
+    /// Adds a transaction filter and returns its index along with the block it takes effect
+    /// from.
+    pub fn add_tx_filter(&self, filter: TransactionFilter) -> (usize, Option<u64>) {
+        let mut index = 0;
+        let block = self.lock().update(|query| {
+            index = query.tx_filters.len();
+            query.tx_filters.push(filter);
+        });
+        (index, block)
+    }
+
+    /// Returns the first block of the next range to be scheduled, from which an update made
+    /// now would take effect, or `None` if every range has been scheduled.
+    pub fn next_block(&self) -> Option<u64> {
+        self.lock().next_block()
+    }
+
+    fn update_log_filter(
+        &self,
+        filter: usize,
+        update: impl FnOnce(&mut LogFilter) -> Result<(), DataStreamError>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        let mut schedule = self.lock();
+        let count = schedule.query.log_filters.len();
+        if filter >= count {
+            return Err(DataStreamError::ValidationError(format!(
+                "no log filter at index {} (the stream has {})",
+                filter, count
+            )));
+        }
+        let mut updated = schedule.query.log_filters[filter].clone();
+        update(&mut updated)?;
+        Ok(schedule.update(|query| query.log_filters[filter] = updated))
+    }
+
+    fn update_tx_filter(
+        &self,
+        filter: usize,
+        update: impl FnOnce(&mut TransactionFilter) -> Result<(), DataStreamError>,
+    ) -> Result<Option<u64>, DataStreamError> {
+        let mut schedule = self.lock();
+        let count = schedule.query.tx_filters.len();
+        if filter >= count {
+            return Err(DataStreamError::ValidationError(format!(
+                "no transaction filter at index {} (the stream has {})",
+                filter, count
+            )));
+        }
+        let mut updated = schedule.query.tx_filters[filter].clone();
+        update(&mut updated)?;
+        Ok(schedule.update(|query| query.tx_filters[filter] = updated))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Schedule> {
+        // The lock is never held across a panic-prone call, so a poisoned lock is still usable.
+        self.schedule
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn add_all<T: PartialEq>(
+    list: &mut Vec<T>,
+    values: impl IntoIterator<Item = T>,
+) -> Result<(), DataStreamError> {
+    for value in values {
+        if !list.contains(&value) {
+            list.push(value);
+        }
+    }
+    Ok(())
+}
+
+/// Removes `values` from `list`, unless that would empty it and widen the filter holding it
+/// to every `what`.
+fn remove_all<T: PartialEq>(
+    list: &mut Vec<T>,
+    values: impl IntoIterator<Item = T>,
+    what: &str,
+) -> Result<(), DataStreamError> {
+    let values: Vec<T> = values.into_iter().collect();
+    if !list.is_empty() && list.iter().all(|value| values.contains(value)) {
+        return Err(DataStreamError::ValidationError(format!(
+            "cannot remove the last {} of a filter, which would then match every {}",
+            what, what
+        )));
+    }
+    list.retain(|value| !values.contains(value));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that updates apply from the next unscheduled range and report its first block.
+    #[test]
+    fn test_filter_updates_take_effect_from_next_range() {
+        let query = QuerySet {
+            log_filters: vec![LogFilter::new().add_address(Address::from_low_u64(1))],
+            ..Default::default()
+        };
+        let schedule = Arc::new(Mutex::new(Schedule::new(
+            query,
+            Vec::new(),
+            vec![(0, 9), (10, 19), (20, 29)],
+            false,
+            100,
+        )));
+        let handle = FilterHandle::new(schedule.clone());
+
+        let (_, _, first) = schedule.lock().unwrap().next_range().unwrap();
+        assert_eq!(
+            handle
+                .add_log_addresses(0, [Address::from_low_u64(2)])
+                .unwrap(),
+            Some(10)
+        );
+        let (start, _, second) = schedule.lock().unwrap().next_range().unwrap();
+        assert_eq!(start, 10);
+        assert_eq!(
+            first[0].log_filters[0].address,
+            vec![Address::from_low_u64(1)]
+        );
+        assert_eq!(
+            second[0].log_filters[0].address,
+            vec![Address::from_low_u64(1), Address::from_low_u64(2)]
+        );
+
+        assert_eq!(
+            handle.add_tx_filter(TransactionFilter::new()),
+            (0, Some(20))
+        );
+        assert!(matches!(
+            handle.remove_log_addresses(3, [Address::from_low_u64(1)]),
+            Err(DataStreamError::ValidationError(_))
+        ));
+
+        assert!(matches!(
+            handle.remove_log_addresses(0, [Address::from_low_u64(1), Address::from_low_u64(2)]),
+            Err(DataStreamError::ValidationError(_))
+        ));
+        assert_eq!(
+            handle
+                .remove_log_addresses(0, [Address::from_low_u64(1)])
+                .unwrap(),
+            Some(20)
+        );
+
+        schedule.lock().unwrap().next_range().unwrap();
+        assert_eq!(handle.next_block(), None);
+        assert_eq!(
+            schedule.lock().unwrap().query.log_filters[0].address,
+            vec![Address::from_low_u64(2)]
+        );
+    }
+
+    /// Test that in reverse, updates report the highest block of the next range.
+    #[test]
+    fn test_filter_updates_in_reverse() {
+        let query = QuerySet {
+            tx_filters: vec![TransactionFilter::new().add_to(Address::from_low_u64(1))],
+            ..Default::default()
+        };
+        let schedule = Arc::new(Mutex::new(Schedule::new(
+            query,
+            Vec::new(),
+            vec![(20, 29), (10, 19), (0, 9)],
+            true,
+            100,
+        )));
+        let handle = FilterHandle::new(schedule.clone());
+
+        schedule.lock().unwrap().next_range().unwrap();
+        assert_eq!(handle.next_block(), Some(19));
+        assert_eq!(
+            handle
+                .add_tx_to_addresses(0, [Address::from_low_u64(2)])
+                .unwrap(),
+            Some(19)
+        );
+        assert!(matches!(
+            handle.remove_tx_to_addresses(0, [Address::from_low_u64(1), Address::from_low_u64(2)]),
+            Err(DataStreamError::ValidationError(_))
+        ));
+        assert_eq!(
+            schedule.lock().unwrap().query.tx_filters[0].to,
+            Some(vec![Address::from_low_u64(1), Address::from_low_u64(2)])
+        );
+    }
+}
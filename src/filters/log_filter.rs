@@ -1,12 +1,19 @@
-use serde::Serialize;
+use crate::errors::DataStreamError;
+use crate::types::{keccak256, Address, B256};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Represents a filter for Ethereum logs based on address and topics.
-#[derive(Clone, Debug)]
+///
+/// When deserialized (e.g. from a `StreamSpec`), topics may be given as event signatures, as
+/// with [`LogFilter::with_topic`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LogFilter {
     /// A list of Ethereum addresses to filter logs by.
-    pub address: Vec<String>,
+    pub address: Vec<Address>,
     /// A list of topics to filter logs by.
-    pub topic0: Vec<String>,
+    #[serde(deserialize_with = "deserialize_topics")]
+    pub topic0: Vec<B256>,
 }
 
 impl LogFilter {
@@ -27,43 +34,89 @@ impl LogFilter {
     }
 
     /// Adds an address to the filter.
-    ///
-    /// Converts the provided address to lowercase before adding it.
+    pub fn add_address(mut self, address: Address) -> Self {
+        self.address.push(address);
+        self
+    }
+
+    /// Adds a topic to the filter's `topic0` field.
+    pub fn add_topic(mut self, topic: B256) -> Self {
+        self.topic0.push(topic);
+        self
+    }
+
+    /// Parses and adds an address to the filter.
     ///
     /// # Parameters
     ///
-    /// * `address` - A reference to the address to be added to the filter.
+    /// * `address` - A hex address, either lowercase or with a valid EIP-55 checksum.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
     ///
     /// # Example
     ///
     /// ```
     /// use subsquid_data_streaming::LogFilter;
     ///
-    /// let filter = LogFilter::new().with_address("0xabcd");
+    /// let filter = LogFilter::new()
+    ///     .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+    ///     .unwrap();
+    /// assert!(LogFilter::new().with_address("0xabcd").is_err());
     /// ```
-    pub fn with_address(mut self, address: &str) -> Self {
-        self.address.push(address.to_lowercase());
-        self
+    pub fn with_address(self, address: &str) -> Result<Self, DataStreamError> {
+        Ok(self.add_address(address.parse()?))
     }
 
-    /// Adds a topic to the filter's `topic` field.
-    ///
-    /// Converts the provided topic to lowercase before adding it.
+    /// Parses and adds a topic to the filter's `topic0` field.
     ///
     /// # Parameters
     ///
-    /// * `topic` - A reference to the topic to be added to the filter.
+    /// * `topic` - Either a 32-byte hex topic, or an event signature such as
+    ///   `Transfer(address,address,uint256)`, which is hashed with Keccak-256.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the topic is neither a valid 32-byte hex
+    /// value nor an event signature.
     ///
     /// # Example
     ///
     /// ```
     /// use subsquid_data_streaming::LogFilter;
     ///
-    /// let filter = LogFilter::new().with_topic("Transfer(address,address,uint256)");
+    /// let filter = LogFilter::new().with_topic("Transfer(address,address,uint256)").unwrap();
+    /// assert_eq!(
+    ///     filter.topic0[0].to_string(),
+    ///     "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+    /// );
     /// ```
-    pub fn with_topic(mut self, topic: &str) -> Self {
-        self.topic0.push(topic.to_lowercase());
-        self
+    pub fn with_topic(self, topic: &str) -> Result<Self, DataStreamError> {
+        Ok(self.add_topic(parse_topic(topic)?))
+    }
+}
+
+/// Parses a 32-byte hex topic, or hashes an event signature into one.
+fn parse_topic(topic: &str) -> Result<B256, DataStreamError> {
+    if topic.contains('(') {
+        Ok(keccak256(topic))
+    } else {
+        topic.parse()
+    }
+}
+
+/// Deserializes a list of topics given as hex values or event signatures.
+fn deserialize_topics<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<B256>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|topic| parse_topic(topic).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -74,10 +127,10 @@ impl LogFilter {
 pub struct LogsFilter {
     /// An optional list of Ethereum addresses to filter logs by.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub address: Option<Vec<String>>,
+    pub address: Option<Vec<Address>>,
     /// An optional list of topic values to filter logs by.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub topic0: Option<Vec<String>>,
+    pub topic0: Option<Vec<B256>>,
     /// Specifies whether the transaction data should be included in the response.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transaction: Option<bool>,
@@ -99,7 +152,10 @@ impl LogsFilter {
     /// use subsquid_data_streaming::LogFilter;
     /// use subsquid_data_streaming::filters::log_filter::LogsFilter;
     ///
-    /// let log_filter = LogFilter::new().with_address("0xabcd").with_topic("Transfer(address,address,uint256)");
+    /// let log_filter = LogFilter::new()
+    ///     .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+    ///     .and_then(|filter| filter.with_topic("Transfer(address,address,uint256)"))
+    ///     .unwrap();
     /// let logs_filter = LogsFilter::from(&log_filter);
     /// ```
     pub fn from(log_filter: &LogFilter) -> Self {
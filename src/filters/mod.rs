@@ -1,9 +1,15 @@
-//! Filters for fetched Ethereum logs and transactions
+//! Filters for fetched Ethereum logs, transactions, traces and state diffs
 
 /// Filters for fetched Ethereum logs
 pub mod log_filter;
+/// Filters for fetched Ethereum state diffs
+pub mod state_diff_filter;
+/// Filters for fetched Ethereum traces
+pub mod trace_filter;
 /// Filters for fetched Ethereum transactions
 pub mod transaction_filter;
 
 pub use log_filter::LogFilter;
+pub use state_diff_filter::{StateDiffFilter, StateDiffKind};
+pub use trace_filter::{TraceFilter, TraceType};
 pub use transaction_filter::TransactionFilter;
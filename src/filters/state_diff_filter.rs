@@ -0,0 +1,144 @@
+use crate::errors::DataStreamError;
+use crate::types::Address;
+use serde::{Deserialize, Serialize};
+
+/// The kind of change recorded by a state diff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StateDiffKind {
+    /// The value was read but not modified (`=`).
+    #[serde(rename = "=")]
+    Unchanged,
+    /// The value was created (`+`).
+    #[serde(rename = "+")]
+    Added,
+    /// The value was modified (`*`).
+    #[serde(rename = "*")]
+    Changed,
+    /// The value was removed (`-`).
+    #[serde(rename = "-")]
+    Deleted,
+}
+
+/// Represents a filter for per-transaction state diffs based on address, key and kind.
+///
+/// The key is either a 32-byte storage slot or one of `balance`, `code` and `nonce`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StateDiffFilter {
+    /// A list of account addresses to filter state diffs by.
+    pub address: Vec<Address>,
+    /// A list of keys (storage slots, `balance`, `code` or `nonce`) to filter state diffs by.
+    pub key: Vec<String>,
+    /// A list of change kinds to filter state diffs by.
+    pub kind: Vec<StateDiffKind>,
+}
+
+impl StateDiffFilter {
+    /// Creates a new `StateDiffFilter` with empty address, key and kind lists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::StateDiffFilter;
+    ///
+    /// let filter = StateDiffFilter::new();
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            address: Vec::new(),
+            key: Vec::new(),
+            kind: Vec::new(),
+        }
+    }
+
+    /// Parses and adds an account address to the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::StateDiffFilter;
+    ///
+    /// let filter = StateDiffFilter::new()
+    ///     .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+    ///     .unwrap();
+    /// ```
+    pub fn with_address(mut self, address: &str) -> Result<Self, DataStreamError> {
+        self.address.push(address.parse()?);
+        Ok(self)
+    }
+
+    /// Adds a key to the filter: a storage slot, `balance`, `code` or `nonce`.
+    ///
+    /// Converts the provided key to lowercase before adding it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::StateDiffFilter;
+    ///
+    /// let filter = StateDiffFilter::new()
+    ///     .with_key("0x0000000000000000000000000000000000000000000000000000000000000003");
+    /// ```
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key.push(key.to_lowercase());
+        self
+    }
+
+    /// Adds a change kind to the filter.
+    pub fn with_kind(mut self, kind: StateDiffKind) -> Self {
+        self.kind.push(kind);
+        self
+    }
+}
+
+impl Default for StateDiffFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Represents a serialized filter for state diffs used in a request to the data lake.
+#[derive(Clone, Debug, Serialize)]
+pub struct StateDiffsFilter {
+    /// An optional list of account addresses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Vec<Address>>,
+    /// An optional list of keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<Vec<String>>,
+    /// An optional list of change kinds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<Vec<StateDiffKind>>,
+}
+
+impl StateDiffsFilter {
+    /// Creates a `StateDiffsFilter` from a `StateDiffFilter`.
+    ///
+    /// # Parameters
+    ///
+    /// * `filter` - A reference to a `StateDiffFilter` instance to convert.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::StateDiffFilter;
+    /// use subsquid_data_streaming::filters::state_diff_filter::StateDiffsFilter;
+    ///
+    /// let filter = StateDiffFilter::new()
+    ///     .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+    ///     .unwrap()
+    ///     .with_key("balance");
+    /// let state_diffs_filter = StateDiffsFilter::from(&filter);
+    /// ```
+    pub fn from(filter: &StateDiffFilter) -> Self {
+        Self {
+            address: (!filter.address.is_empty()).then(|| filter.address.clone()),
+            key: (!filter.key.is_empty()).then(|| filter.key.clone()),
+            kind: (!filter.kind.is_empty()).then(|| filter.kind.clone()),
+        }
+    }
+}
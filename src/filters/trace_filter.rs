@@ -0,0 +1,230 @@
+use crate::errors::DataStreamError;
+use crate::types::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+
+/// The kind of a trace (internal call) recorded while executing a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceType {
+    /// A contract creation (`CREATE` / `CREATE2`).
+    Create,
+    /// A message call, including internal ETH transfers.
+    Call,
+    /// A `SELFDESTRUCT`.
+    Suicide,
+    /// A block or uncle reward.
+    Reward,
+}
+
+/// Represents a filter for Ethereum traces based on their type, caller, callee and function selector.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraceFilter {
+    /// A list of trace types to filter traces by.
+    pub trace_type: Vec<TraceType>,
+    /// A list of addresses that created a contract.
+    pub create_from: Vec<Address>,
+    /// A list of addresses that made a call.
+    pub call_from: Vec<Address>,
+    /// A list of addresses that received a call.
+    pub call_to: Vec<Address>,
+    /// A list of 4-byte function selectors of calls.
+    pub call_sighash: Vec<Bytes>,
+    /// A list of addresses receiving the balance of a self-destructed contract.
+    pub suicide_refund_address: Vec<Address>,
+    /// A list of addresses receiving a block reward.
+    pub reward_author: Vec<Address>,
+}
+
+impl TraceFilter {
+    /// Creates a new `TraceFilter` that matches no particular type or address.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::TraceFilter;
+    ///
+    /// let filter = TraceFilter::new();
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            trace_type: Vec::new(),
+            create_from: Vec::new(),
+            call_from: Vec::new(),
+            call_to: Vec::new(),
+            call_sighash: Vec::new(),
+            suicide_refund_address: Vec::new(),
+            reward_author: Vec::new(),
+        }
+    }
+
+    /// Adds a trace type to the filter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::{TraceFilter, TraceType};
+    ///
+    /// let filter = TraceFilter::new().with_type(TraceType::Create);
+    /// ```
+    pub fn with_type(mut self, trace_type: TraceType) -> Self {
+        self.trace_type.push(trace_type);
+        self
+    }
+
+    /// Parses and adds a contract creator address to the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
+    pub fn with_create_from(mut self, address: &str) -> Result<Self, DataStreamError> {
+        self.create_from.push(address.parse()?);
+        Ok(self)
+    }
+
+    /// Parses and adds a caller address to the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::TraceFilter;
+    ///
+    /// let filter = TraceFilter::new()
+    ///     .with_call_from("0x7a250d5630b4cf539739df2c5dacb4c659f2488d")
+    ///     .unwrap();
+    /// ```
+    pub fn with_call_from(mut self, address: &str) -> Result<Self, DataStreamError> {
+        self.call_from.push(address.parse()?);
+        Ok(self)
+    }
+
+    /// Parses and adds a callee address to the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
+    pub fn with_call_to(mut self, address: &str) -> Result<Self, DataStreamError> {
+        self.call_to.push(address.parse()?);
+        Ok(self)
+    }
+
+    /// Parses and adds a 4-byte function selector (e.g. `0xa9059cbb`) to the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the selector is not 4 bytes of hex.
+    pub fn with_sighash(mut self, sighash: &str) -> Result<Self, DataStreamError> {
+        let sighash: Bytes = sighash.parse()?;
+        if sighash.len() != 4 {
+            return Err(DataStreamError::ValidationError(format!(
+                "invalid sighash {}: expected 4 bytes, got {}",
+                sighash,
+                sighash.len()
+            )));
+        }
+        self.call_sighash.push(sighash);
+        Ok(self)
+    }
+
+    /// Parses and adds a self-destruct refund address to the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
+    pub fn with_suicide_refund_address(mut self, address: &str) -> Result<Self, DataStreamError> {
+        self.suicide_refund_address.push(address.parse()?);
+        Ok(self)
+    }
+
+    /// Parses and adds a block reward recipient to the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
+    pub fn with_reward_author(mut self, address: &str) -> Result<Self, DataStreamError> {
+        self.reward_author.push(address.parse()?);
+        Ok(self)
+    }
+}
+
+impl Default for TraceFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Represents a serialized filter for trace data used in a request to the data lake.
+#[derive(Clone, Debug, Serialize)]
+pub struct TracesFilter {
+    /// An optional list of trace types.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub trace_type: Option<Vec<TraceType>>,
+    /// An optional list of contract creator addresses.
+    #[serde(rename = "createFrom", skip_serializing_if = "Option::is_none")]
+    pub create_from: Option<Vec<Address>>,
+    /// An optional list of caller addresses.
+    #[serde(rename = "callFrom", skip_serializing_if = "Option::is_none")]
+    pub call_from: Option<Vec<Address>>,
+    /// An optional list of callee addresses.
+    #[serde(rename = "callTo", skip_serializing_if = "Option::is_none")]
+    pub call_to: Option<Vec<Address>>,
+    /// An optional list of function selectors.
+    #[serde(rename = "callSighash", skip_serializing_if = "Option::is_none")]
+    pub call_sighash: Option<Vec<Bytes>>,
+    /// An optional list of self-destruct refund addresses.
+    #[serde(
+        rename = "suicideRefundAddress",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub suicide_refund_address: Option<Vec<Address>>,
+    /// An optional list of block reward recipients.
+    #[serde(rename = "rewardAuthor", skip_serializing_if = "Option::is_none")]
+    pub reward_author: Option<Vec<Address>>,
+}
+
+impl TracesFilter {
+    /// Creates a `TracesFilter` from a `TraceFilter`.
+    ///
+    /// Empty lists are omitted from the request so that they act as wildcards.
+    ///
+    /// # Parameters
+    ///
+    /// * `filter` - A reference to a `TraceFilter` instance to convert.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::{TraceFilter, TraceType};
+    /// use subsquid_data_streaming::filters::trace_filter::TracesFilter;
+    ///
+    /// let trace_filter = TraceFilter::new()
+    ///     .with_type(TraceType::Call)
+    ///     .with_call_to("0x7a250d5630b4cf539739df2c5dacb4c659f2488d")
+    ///     .unwrap();
+    /// let traces_filter = TracesFilter::from(&trace_filter);
+    /// ```
+    pub fn from(filter: &TraceFilter) -> Self {
+        Self {
+            trace_type: non_empty(&filter.trace_type),
+            create_from: non_empty(&filter.create_from),
+            call_from: non_empty(&filter.call_from),
+            call_to: non_empty(&filter.call_to),
+            call_sighash: non_empty(&filter.call_sighash),
+            suicide_refund_address: non_empty(&filter.suicide_refund_address),
+            reward_author: non_empty(&filter.reward_author),
+        }
+    }
+}
+
+/// Returns a copy of `values`, or `None` if it is empty.
+fn non_empty<T: Clone>(values: &[T]) -> Option<Vec<T>> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.to_vec())
+    }
+}
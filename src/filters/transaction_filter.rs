@@ -1,12 +1,15 @@
-use serde::Serialize;
+use crate::errors::DataStreamError;
+use crate::types::Address;
+use serde::{Deserialize, Serialize};
 
 /// Represents a filter for Ethereum transactions based on `from` and `to` addresses.
-#[derive(Clone, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TransactionFilter {
     /// A list of Ethereum addresses that the transaction originated from.
-    pub from: Option<Vec<String>>,
+    pub from: Option<Vec<Address>>,
     /// A list of Ethereum addresses that the transaction is sent to.
-    pub to: Option<Vec<String>>,
+    pub to: Option<Vec<Address>>,
 }
 
 impl TransactionFilter {
@@ -28,48 +31,68 @@ impl TransactionFilter {
 
     /// Adds an address to the `from` field of the transaction filter.
     ///
-    /// Converts the provided address to lowercase before adding it.
     /// If the `from` field is `None`, it initializes it as an empty `Vec`.
+    pub fn add_from(mut self, address: Address) -> Self {
+        self.from.get_or_insert(Vec::new()).push(address);
+        self
+    }
+
+    /// Adds an address to the `to` field of the transaction filter.
+    ///
+    /// If the `to` field is `None`, it initializes it as an empty `Vec`.
+    pub fn add_to(mut self, address: Address) -> Self {
+        self.to.get_or_insert(Vec::new()).push(address);
+        self
+    }
+
+    /// Parses and adds an address to the `from` field of the transaction filter.
     ///
     /// # Parameters
     ///
-    /// * `address` - A reference to the address to be added to the `from` field.
+    /// * `address` - A hex address, either lowercase or with a valid EIP-55 checksum.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
     ///
     /// # Example
     ///
     /// ```
     /// use subsquid_data_streaming::TransactionFilter;
     ///
-    /// let filter = TransactionFilter::new().with_from("0xabcd");
+    /// let filter = TransactionFilter::new()
+    ///     .with_from("0x6e869cadc1cb3d4c6291e6e939b5b55d51c69084")
+    ///     .unwrap();
     /// ```
-    pub fn with_from(mut self, address: &str) -> Self {
-        self.from
-            .get_or_insert(Vec::new())
-            .push(address.to_lowercase());
-        self
+    pub fn with_from(self, address: &str) -> Result<Self, DataStreamError> {
+        Ok(self.add_from(address.parse()?))
     }
 
-    /// Adds an address to the `to` field of the transaction filter.
-    ///
-    /// Converts the provided address to lowercase before adding it.
-    /// If the `to` field is `None`, it initializes it as an empty `Vec`.
+    /// Parses and adds an address to the `to` field of the transaction filter.
     ///
     /// # Parameters
     ///
-    /// * `address` - A reference to the address to be added to the `to` field.
+    /// * `address` - A hex address, either lowercase or with a valid EIP-55 checksum.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ValidationError` if the address is not valid.
     ///
     /// # Example
     ///
     /// ```
     /// use subsquid_data_streaming::TransactionFilter;
     ///
-    /// let filter = TransactionFilter::new().with_to("0xefgh");
+    /// assert!(TransactionFilter::new().with_to("0xefgh").is_err());
     /// ```
-    pub fn with_to(mut self, address: &str) -> Self {
-        self.to
-            .get_or_insert(Vec::new())
-            .push(address.to_lowercase());
-        self
+    pub fn with_to(self, address: &str) -> Result<Self, DataStreamError> {
+        Ok(self.add_to(address.parse()?))
+    }
+}
+
+impl Default for TransactionFilter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -80,10 +103,10 @@ impl TransactionFilter {
 pub struct TransactionsFilter {
     /// An optional list of Ethereum addresses that the transaction originated from.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub from: Option<Vec<String>>,
+    pub from: Option<Vec<Address>>,
     /// An optional list of Ethereum addresses that the transaction is sent to.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub to: Option<Vec<String>>,
+    pub to: Option<Vec<Address>>,
 }
 
 impl TransactionsFilter {
@@ -101,7 +124,9 @@ impl TransactionsFilter {
     /// use subsquid_data_streaming::TransactionFilter;
     /// use subsquid_data_streaming::filters::transaction_filter::TransactionsFilter;
     ///
-    /// let tx_filter = TransactionFilter::new().with_from("0xabcd");
+    /// let tx_filter = TransactionFilter::new()
+    ///     .with_from("0x6e869cadc1cb3d4c6291e6e939b5b55d51c69084")
+    ///     .unwrap();
     /// let transactions_filter = TransactionsFilter::from(&tx_filter);
     /// ```
     pub fn from(filter: &TransactionFilter) -> Self {
@@ -1,14 +1,17 @@
 //! Subsquid Rust on-chain data streaming library.
 //!
-//! This library is designed to provide a data stream for blockchain events like logs and transactions,
-//! utilizing filters and options to narrow down the data. It can interact with workers that fetch data
+//! This library is designed to provide a data stream for blockchain events like logs, transactions, traces
+//! and state diffs, utilizing filters and options to narrow down the data. It can interact with workers that fetch data
 //! from the blockchain, and users can specify block ranges, filters, and data field selections.
 //!
 //! The core components are:
 //! - **DataStream**: The main streaming structure to fetch blockchain data in real-time.
-//! - **Filters**: Used to define what logs and transactions to capture.
+//! - **Filters**: Used to define what logs, transactions, traces and state diffs to capture.
 //! - **Options**: Used to define what data fields to include in the result (topics, data, transaction hash, etc.).
 
+// Lets the code generated by the derive macros refer to `::subsquid_data_streaming` from within this crate.
+extern crate self as subsquid_data_streaming;
+
 /// Defines the supported data sources (e.g., Subsquid, EVM RPC).
 pub mod data_source;
 
@@ -18,18 +21,42 @@ pub mod data_stream;
 /// Error handling definitions for the library.
 pub mod errors;
 
-/// Filtering mechanisms for logs and transactions.
+/// Filtering mechanisms for logs, transactions, traces and state diffs.
 pub mod filters;
 
-/// Models representing logs, transactions, and block data.
+/// Models representing logs, transactions, traces, state diffs, and block data.
 pub mod models;
 
 /// Options to define which fields (topics, data, etc.) should be returned.
 pub mod fields;
 
+/// Client-side filters applied to fetched items before they reach the stream.
+pub mod predicates;
+
+/// Declarative stream definitions loadable from TOML, YAML or JSON.
+pub mod spec;
+
 /// Client responsible for interacting with the router to get worker URLs.
 pub mod router_client;
 
+/// Splitting of oversized queries and merging of their results.
+mod query_plan;
+
+/// Handle to change the filters of a running stream.
+pub mod filter_handle;
+
+/// Tracking of contracts created by a factory contract.
+pub mod factory;
+
+/// Named sets of filters fetched over the same stream.
+pub mod named_query;
+
+/// Several streams on different chains driven as one.
+pub mod multi_chain;
+
+/// Resolution of timestamps to block numbers.
+mod block_time;
+
 /// Utility functions used in parsing or handling block ranges.
 mod utils;
 
@@ -39,9 +66,24 @@ pub mod worker_client;
 /// Structure defining the worker query.
 pub mod worker_query;
 
+/// Primitive EVM types such as addresses, hashes, byte strings and 256-bit integers.
+pub mod types;
+
+/// Decoding of event logs into named, typed parameters using a contract ABI.
+pub mod abi;
+
+pub use abi::EvmEvent; // Typed event bindings, usually derived with `#[derive(EvmEvent)]`.
 pub use data_source::DataSource; // Represents the supported data sources (e.g., Subsquid).
 pub use data_stream::DataStream; // The main structure for building and managing the data stream.
 pub use errors::DataStreamError; // Errors that can be encountered during streaming.
-pub use fields::{LogFields, TransactionFields};
-pub use filters::{LogFilter, TransactionFilter}; // Log and transaction filters.
-pub use models::{LogEntry, TransactionEntry}; // Structures representing logs and transactions. // Options for selecting fields in logs and transactions.
+pub use factory::Factory; // Tracks the children of a factory contract.
+pub use fields::{BlockFields, LogFields, StateDiffFields, TraceFields, TransactionFields}; // Options for selecting fields in logs and transactions.
+pub use filter_handle::FilterHandle; // Changes the filters of a running stream.
+pub use filters::{
+    LogFilter, StateDiffFilter, StateDiffKind, TraceFilter, TraceType, TransactionFilter,
+}; // Log, transaction, trace and state diff filters.
+pub use models::{BlockContext, LogEntry, StateDiffEntry, TraceEntry, TransactionEntry, WithBlock}; // Structures representing logs, transactions, traces and state diffs.
+pub use multi_chain::{ChainBatch, MultiChainStream}; // Streams several chains as one.
+pub use named_query::NamedQuery; // Named sets of filters whose items are tagged with the name.
+pub use subsquid_data_streaming_derive::EvmEvent; // Derives `EvmEvent` and `TryFrom<&LogEntry>` for event structs.
+pub use types::{Address, Bytes, B256, U256}; // Primitive EVM types for addresses, hashes, bytes and quantities.
@@ -0,0 +1,52 @@
+use super::BlockHeader;
+use crate::types::B256;
+use std::ops::Deref;
+
+/// Identifies the block an item was taken from.
+///
+/// `hash` and `timestamp` are only set when selected with `BlockFields`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockContext {
+    /// The block number.
+    pub number: u64,
+    /// The block hash.
+    pub hash: Option<B256>,
+    /// The block timestamp, in seconds since the Unix epoch.
+    pub timestamp: Option<u64>,
+}
+
+impl From<&BlockHeader> for BlockContext {
+    fn from(header: &BlockHeader) -> Self {
+        Self {
+            number: header.number,
+            hash: header.hash,
+            timestamp: header.timestamp,
+        }
+    }
+}
+
+/// A log, transaction or other item together with the block it was taken from.
+///
+/// Dereferences to the item, so its fields can be accessed directly.
+#[derive(Clone, Debug)]
+pub struct WithBlock<T> {
+    /// The block the item belongs to.
+    pub block: BlockContext,
+    /// The item itself.
+    pub item: T,
+}
+
+impl<T> WithBlock<T> {
+    /// Returns the item, discarding the block context.
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+}
+
+impl<T> Deref for WithBlock<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
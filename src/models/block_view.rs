@@ -0,0 +1,314 @@
+use super::{BlockHeader, DataItem, LogEntry, StateDiffEntry, TraceEntry, TransactionEntry};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// An indexed view of a `DataItem` that joins logs, traces and state diffs to the transactions
+/// that produced them.
+///
+/// The index is built once, when the view is created with [`DataItem::view`], and keyed by
+/// `transaction_index`. Select the `transaction_index` field of every kind of item involved for
+/// the joins to work. Items whose transaction is not part of the block data simply have no
+/// parent transaction.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run(item: &subsquid_data_streaming::models::DataItem) {
+/// let block = item.view();
+/// for transaction in block.transactions() {
+///     println!("{:?} emitted {} logs", transaction.hash, transaction.logs().count());
+/// }
+/// for log in block.logs() {
+///     if let Some(transaction) = log.transaction() {
+///         println!("log {} was emitted by {:?}", log.log_index, transaction.hash);
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BlockView<'a> {
+    item: &'a DataItem,
+    transactions: HashMap<u64, usize>,
+    logs: HashMap<u64, Vec<usize>>,
+    traces: HashMap<u64, Vec<usize>>,
+    state_diffs: HashMap<u64, Vec<usize>>,
+}
+
+impl<'a> BlockView<'a> {
+    /// Builds the view, indexing every item of `item` by its transaction index.
+    pub fn new(item: &'a DataItem) -> Self {
+        let transactions = item
+            .transactions
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, tx)| tx.transaction_index.map(|index| (index, i)))
+            .collect();
+        let logs = group_by(item.logs.as_deref(), |log| Some(log.transaction_index));
+        let traces = group_by(item.traces.as_deref(), |trace| trace.transaction_index);
+        let state_diffs = group_by(item.state_diffs.as_deref(), |diff| diff.transaction_index);
+        Self {
+            item,
+            transactions,
+            logs,
+            traces,
+            state_diffs,
+        }
+    }
+
+    /// Returns the block header.
+    pub fn header(&self) -> &'a BlockHeader {
+        &self.item.header
+    }
+
+    /// Returns the transactions of the block, in the order they were returned.
+    pub fn transactions(&self) -> impl Iterator<Item = TransactionView<'_>> {
+        entries(&self.item.transactions).map(move |entry| TransactionView { block: self, entry })
+    }
+
+    /// Returns the transaction with the given index within the block.
+    pub fn transaction(&self, transaction_index: u64) -> Option<TransactionView<'_>> {
+        let &i = self.transactions.get(&transaction_index)?;
+        Some(TransactionView {
+            block: self,
+            entry: &self.item.transactions.as_ref()?[i],
+        })
+    }
+
+    /// Returns the logs of the block, in the order they were returned.
+    pub fn logs(&self) -> impl Iterator<Item = LogView<'_>> {
+        entries(&self.item.logs).map(move |entry| LogView { block: self, entry })
+    }
+
+    /// Returns the traces of the block, in the order they were returned.
+    pub fn traces(&self) -> impl Iterator<Item = TraceView<'_>> {
+        entries(&self.item.traces).map(move |entry| TraceView { block: self, entry })
+    }
+
+    /// Returns the state diffs of the block, in the order they were returned.
+    pub fn state_diffs(&self) -> impl Iterator<Item = StateDiffView<'_>> {
+        entries(&self.item.state_diffs).map(move |entry| StateDiffView { block: self, entry })
+    }
+}
+
+/// A transaction within a [`BlockView`].
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionView<'a> {
+    block: &'a BlockView<'a>,
+    entry: &'a TransactionEntry,
+}
+
+impl<'a> TransactionView<'a> {
+    /// Returns the underlying transaction.
+    pub fn entry(&self) -> &'a TransactionEntry {
+        self.entry
+    }
+
+    /// Returns the logs emitted by this transaction.
+    pub fn logs(&self) -> impl Iterator<Item = LogView<'a>> {
+        let block = self.block;
+        children(&block.logs, &block.item.logs, self.entry.transaction_index)
+            .map(move |entry| LogView { block, entry })
+    }
+
+    /// Returns the traces produced by this transaction.
+    pub fn traces(&self) -> impl Iterator<Item = TraceView<'a>> {
+        let block = self.block;
+        children(
+            &block.traces,
+            &block.item.traces,
+            self.entry.transaction_index,
+        )
+        .map(move |entry| TraceView { block, entry })
+    }
+
+    /// Returns the state changes made by this transaction.
+    pub fn state_diffs(&self) -> impl Iterator<Item = StateDiffView<'a>> {
+        let block = self.block;
+        children(
+            &block.state_diffs,
+            &block.item.state_diffs,
+            self.entry.transaction_index,
+        )
+        .map(move |entry| StateDiffView { block, entry })
+    }
+}
+
+impl Deref for TransactionView<'_> {
+    type Target = TransactionEntry;
+
+    fn deref(&self) -> &TransactionEntry {
+        self.entry
+    }
+}
+
+/// A log within a [`BlockView`].
+#[derive(Clone, Copy, Debug)]
+pub struct LogView<'a> {
+    block: &'a BlockView<'a>,
+    entry: &'a LogEntry,
+}
+
+impl<'a> LogView<'a> {
+    /// Returns the underlying log.
+    pub fn entry(&self) -> &'a LogEntry {
+        self.entry
+    }
+
+    /// Returns the transaction that emitted this log, if it is part of the block data.
+    pub fn transaction(&self) -> Option<TransactionView<'a>> {
+        self.block.transaction(self.entry.transaction_index)
+    }
+}
+
+impl Deref for LogView<'_> {
+    type Target = LogEntry;
+
+    fn deref(&self) -> &LogEntry {
+        self.entry
+    }
+}
+
+/// A trace within a [`BlockView`].
+#[derive(Clone, Copy, Debug)]
+pub struct TraceView<'a> {
+    block: &'a BlockView<'a>,
+    entry: &'a TraceEntry,
+}
+
+impl<'a> TraceView<'a> {
+    /// Returns the underlying trace.
+    pub fn entry(&self) -> &'a TraceEntry {
+        self.entry
+    }
+
+    /// Returns the transaction that produced this trace, if it is part of the block data.
+    pub fn transaction(&self) -> Option<TransactionView<'a>> {
+        self.block.transaction(self.entry.transaction_index?)
+    }
+}
+
+impl Deref for TraceView<'_> {
+    type Target = TraceEntry;
+
+    fn deref(&self) -> &TraceEntry {
+        self.entry
+    }
+}
+
+/// A state diff within a [`BlockView`].
+#[derive(Clone, Copy, Debug)]
+pub struct StateDiffView<'a> {
+    block: &'a BlockView<'a>,
+    entry: &'a StateDiffEntry,
+}
+
+impl<'a> StateDiffView<'a> {
+    /// Returns the underlying state diff.
+    pub fn entry(&self) -> &'a StateDiffEntry {
+        self.entry
+    }
+
+    /// Returns the transaction that made this change, if it is part of the block data.
+    pub fn transaction(&self) -> Option<TransactionView<'a>> {
+        self.block.transaction(self.entry.transaction_index?)
+    }
+}
+
+impl Deref for StateDiffView<'_> {
+    type Target = StateDiffEntry;
+
+    fn deref(&self) -> &StateDiffEntry {
+        self.entry
+    }
+}
+
+/// Returns the entries of an optional list.
+fn entries<T>(list: &Option<Vec<T>>) -> std::slice::Iter<'_, T> {
+    list.as_deref().unwrap_or_default().iter()
+}
+
+/// Groups the positions of `items` by transaction index, skipping items without one.
+fn group_by<T>(items: Option<&[T]>, key: impl Fn(&T) -> Option<u64>) -> HashMap<u64, Vec<usize>> {
+    let mut groups: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, item) in items.unwrap_or_default().iter().enumerate() {
+        if let Some(index) = key(item) {
+            groups.entry(index).or_default().push(i);
+        }
+    }
+    groups
+}
+
+/// Returns the items of `list` indexed under `transaction_index` in `groups`.
+fn children<'a, T>(
+    groups: &'a HashMap<u64, Vec<usize>>,
+    list: &'a Option<Vec<T>>,
+    transaction_index: Option<u64>,
+) -> impl Iterator<Item = &'a T> {
+    let positions = transaction_index
+        .and_then(|index| groups.get(&index))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let list = list.as_deref().unwrap_or_default();
+    positions.iter().map(move |&i| &list[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that logs, traces and state diffs are joined to their transactions both ways.
+    #[test]
+    fn test_block_view_joins() {
+        let item: DataItem = serde_json::from_value(serde_json::json!({
+            "header": { "number": 1 },
+            "transactions": [
+                { "transactionIndex": 0, "nonce": 10 },
+                { "transactionIndex": 2, "nonce": 12 },
+            ],
+            "logs": [
+                { "transactionIndex": 2, "logIndex": 0 },
+                { "transactionIndex": 0, "logIndex": 1 },
+                { "transactionIndex": 2, "logIndex": 2 },
+                { "transactionIndex": 5, "logIndex": 3 },
+            ],
+            "traces": [
+                { "transactionIndex": 2, "traceAddress": [] },
+                { "traceAddress": [] },
+            ],
+            "stateDiffs": [
+                { "transactionIndex": 0, "key": "balance" },
+            ],
+        }))
+        .unwrap();
+        let block = item.view();
+        assert_eq!(block.header().number, 1);
+
+        let logs_per_tx: Vec<(Option<u64>, Vec<u64>)> = block
+            .transactions()
+            .map(|tx| (tx.nonce, tx.logs().map(|log| log.log_index).collect()))
+            .collect();
+        assert_eq!(
+            logs_per_tx,
+            vec![(Some(10), vec![1]), (Some(12), vec![0, 2])]
+        );
+
+        let parents: Vec<Option<u64>> = block
+            .logs()
+            .map(|log| log.transaction().and_then(|tx| tx.nonce))
+            .collect();
+        assert_eq!(parents, vec![Some(12), Some(10), Some(12), None]);
+
+        let tx = block.transaction(2).unwrap();
+        assert_eq!(tx.traces().count(), 1);
+        assert_eq!(tx.state_diffs().count(), 0);
+        let traces: Vec<_> = block.traces().collect();
+        assert_eq!(traces[0].transaction().unwrap().nonce, Some(12));
+        assert!(traces[1].transaction().is_none());
+
+        let diff = block.state_diffs().next().unwrap();
+        assert_eq!(diff.transaction().unwrap().nonce, Some(10));
+        assert_eq!(block.transaction(0).unwrap().state_diffs().count(), 1);
+    }
+}
@@ -1,27 +1,147 @@
-use super::{LogEntry, TransactionEntry};
-use serde::Deserialize;
+use super::{BlockView, LogEntry, StateDiffEntry, TraceEntry, TransactionEntry};
+use crate::types::{Address, Bytes, B256, U256};
+use serde::{Deserialize, Serialize};
 
-/// Represents a single data item containing a block header, logs, and transactions.
+/// Represents a single data item containing a block header, logs, transactions, traces, and state diffs.
 ///
 /// This struct is used to deserialize a data item that contains information about a block
-/// and optionally includes logs and transactions.
-#[derive(Debug, Deserialize)]
+/// and optionally includes logs, transactions, traces and state diffs.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DataItem {
     /// The block header containing metadata about the block.
     pub header: BlockHeader,
     /// Optional list of log entries related to the block.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub logs: Option<Vec<LogEntry>>,
     /// Optional list of transaction entries related to the block.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub transactions: Option<Vec<TransactionEntry>>,
+    /// Optional list of trace entries related to the block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub traces: Option<Vec<TraceEntry>>,
+    /// Optional list of state diff entries related to the block.
+    #[serde(rename = "stateDiffs", skip_serializing_if = "Option::is_none")]
+    pub state_diffs: Option<Vec<StateDiffEntry>>,
+}
+
+impl DataItem {
+    /// Returns an indexed view of this block that joins logs, traces and state diffs to their
+    /// transactions.
+    ///
+    /// The index is built on every call, so keep the view around instead of calling this per
+    /// lookup.
+    pub fn view(&self) -> BlockView<'_> {
+        BlockView::new(self)
+    }
 }
 
 /// Represents the header of a block in the blockchain.
 ///
-/// The block header includes the block number and other potential metadata.
-#[derive(Debug, Deserialize)]
+/// The block number is always present. The remaining fields are only populated when selected
+/// with `BlockFields`.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BlockHeader {
     /// The block number of this block.
     pub number: u64,
+    /// The hash of this block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<B256>,
+    /// The hash of the parent block.
+    #[serde(
+        default,
+        rename = "parentHash",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub parent_hash: Option<B256>,
+    /// The block timestamp, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// The root of the transactions trie.
+    #[serde(
+        default,
+        rename = "transactionsRoot",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transactions_root: Option<B256>,
+    /// The root of the receipts trie.
+    #[serde(
+        default,
+        rename = "receiptsRoot",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub receipts_root: Option<B256>,
+    /// The root of the state trie after this block.
+    #[serde(default, rename = "stateRoot", skip_serializing_if = "Option::is_none")]
+    pub state_root: Option<B256>,
+    /// The bloom filter of the logs emitted in this block.
+    #[serde(default, rename = "logsBloom", skip_serializing_if = "Option::is_none")]
+    pub logs_bloom: Option<Bytes>,
+    /// The hash of the uncles list.
+    #[serde(
+        default,
+        rename = "sha3Uncles",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sha3_uncles: Option<B256>,
+    /// Arbitrary data included by the block producer.
+    #[serde(default, rename = "extraData", skip_serializing_if = "Option::is_none")]
+    pub extra_data: Option<Bytes>,
+    /// The address receiving the block's priority fees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub miner: Option<Address>,
+    /// The proof-of-work nonce.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<Bytes>,
+    /// The proof-of-work mix hash, or the beacon chain randomness after the merge.
+    #[serde(default, rename = "mixHash", skip_serializing_if = "Option::is_none")]
+    pub mix_hash: Option<B256>,
+    /// The size of the block in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// The maximum gas allowed in this block.
+    #[serde(default, rename = "gasLimit", skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U256>,
+    /// The total gas used by all transactions in this block.
+    #[serde(default, rename = "gasUsed", skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<U256>,
+    /// The proof-of-work difficulty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<U256>,
+    /// The total difficulty of the chain up to this block.
+    #[serde(
+        default,
+        rename = "totalDifficulty",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub total_difficulty: Option<U256>,
+    /// The EIP-1559 base fee per gas.
+    #[serde(
+        default,
+        rename = "baseFeePerGas",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub base_fee_per_gas: Option<U256>,
+    /// The total blob gas used by transactions in this block (EIP-4844).
+    #[serde(
+        default,
+        rename = "blobGasUsed",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub blob_gas_used: Option<U256>,
+    /// The running excess of blob gas above the target (EIP-4844).
+    #[serde(
+        default,
+        rename = "excessBlobGas",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub excess_blob_gas: Option<U256>,
+    /// The L1 block number, for L2 chains that report it.
+    #[serde(
+        default,
+        rename = "l1BlockNumber",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub l1_block_number: Option<u64>,
 }
 
 /// Returns the block number of the last item in the provided list of `DataItem`s.
@@ -47,3 +167,123 @@ pub struct BlockHeader {
 pub fn last_block_number(data_items: &[DataItem]) -> Option<u64> {
     data_items.last().map(|item| item.header.number)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a fully selected block header deserializes from the worker's wire format.
+    #[test]
+    fn test_block_header_deserialization() {
+        let json = r#"{
+            "number": 20000000,
+            "hash": "0xd24fd73f794058a3807db926d8898c6481e902b7edb91ce0d479d6760f276183",
+            "parentHash": "0x13b0b7d4b6b7b5e0b1b5d3c1e2a3f4b5c6d7e8f90a1b2c3d4e5f60718293a4b5",
+            "timestamp": 1717281407,
+            "miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+            "size": 82764,
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0xa9371c",
+            "baseFeePerGas": "0x1b4d1e8c0",
+            "totalDifficulty": "0xc70d815d562d3cfa955"
+        }"#;
+        let header: BlockHeader = serde_json::from_str(json).unwrap();
+        assert_eq!(header.number, 20_000_000);
+        assert_eq!(header.timestamp, Some(1_717_281_407));
+        assert_eq!(header.gas_limit, Some(U256::from(30_000_000u64)));
+        assert_eq!(header.gas_used, Some(U256::from(11_089_692u64)));
+        assert_eq!(header.base_fee_per_gas, Some(U256::from(7_328_622_784u64)));
+        assert_eq!(
+            header.total_difficulty.unwrap().to_string(),
+            "58750003716598352816469"
+        );
+        assert_eq!(header.size, Some(82_764));
+        assert!(header.state_root.is_none());
+    }
+
+    /// Test that serializing a block with every kind of item reproduces the worker's JSON, so
+    /// that the output round-trips through the deserializers.
+    #[test]
+    fn test_data_item_round_trip() {
+        let json = serde_json::json!({
+            "header": {
+                "number": 20000000,
+                "hash": "0xd24fd73f794058a3807db926d8898c6481e902b7edb91ce0d479d6760f276183",
+                "timestamp": 1717281407,
+                "miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+                "gasLimit": "0x1c9c380",
+                "baseFeePerGas": "0x1b4d1e8c0"
+            },
+            "logs": [{
+                "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+                "data": "0x00ff",
+                "transactionIndex": 3,
+                "logIndex": 7,
+                "address": "0xdac17f958d2ee523a2206206994597c13d831ec7",
+                "blockNumber": 20000000,
+                "blockHash": "0xd24fd73f794058a3807db926d8898c6481e902b7edb91ce0d479d6760f276183",
+                "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "removed": false
+            }],
+            "transactions": [{
+                "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "transactionIndex": 3,
+                "from": "0x6e869cadc1cb3d4c6291e6e939b5b55d51c69084",
+                "to": "0xdac17f958d2ee523a2206206994597c13d831ec7",
+                "value": "0xde0b6b3a7640000",
+                "input": "0xa9059cbb",
+                "type": 2,
+                "status": 1,
+                "accessList": [{
+                    "address": "0xdac17f958d2ee523a2206206994597c13d831ec7",
+                    "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000002"]
+                }]
+            }],
+            "traces": [{
+                "transactionIndex": 3,
+                "traceAddress": [0],
+                "type": "call",
+                "subtraces": 0,
+                "action": {
+                    "from": "0x6e869cadc1cb3d4c6291e6e939b5b55d51c69084",
+                    "value": "0x0",
+                    "callType": "call"
+                },
+                "result": { "gasUsed": "0x5208", "output": "0x" }
+            }],
+            "stateDiffs": [{
+                "transactionIndex": 3,
+                "address": "0xdac17f958d2ee523a2206206994597c13d831ec7",
+                "key": "balance",
+                "kind": "*",
+                "prev": "0x1",
+                "next": "0x2"
+            }]
+        });
+
+        let item: DataItem = serde_json::from_value(json.clone()).unwrap();
+        let serialized = serde_json::to_value(&item).unwrap();
+        assert_eq!(serialized, json);
+
+        let again: DataItem = serde_json::from_value(serialized).unwrap();
+        assert_eq!(serde_json::to_value(&again).unwrap(), json);
+    }
+
+    /// Test that fields that were not selected are not made up when re-serializing.
+    #[test]
+    fn test_partial_log_round_trip() {
+        let json = serde_json::json!({
+            "header": { "number": 20000000 },
+            "logs": [{
+                "topics": ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+                "transactionIndex": 3,
+                "logIndex": 7
+            }]
+        });
+
+        let item: DataItem = serde_json::from_value(json.clone()).unwrap();
+        let log = &item.logs.as_ref().unwrap()[0];
+        assert!(log.address.is_none() && log.data.is_none() && log.block_hash.is_none());
+        assert_eq!(serde_json::to_value(&item).unwrap(), json);
+    }
+}
@@ -1,31 +1,45 @@
-use serde::Deserialize;
+use crate::types::{Address, Bytes, B256};
+use serde::{Deserialize, Serialize};
 
 /// Represents a log entry from a transaction in a block.
 ///
 /// This struct is used to deserialize log data, which contains information about specific
-/// events emitted by contracts during the execution of a transaction.
-#[derive(Debug, Deserialize, Clone)]
+/// events emitted by contracts during the execution of a transaction. Fields that were not
+/// selected are `None` and omitted when serialized.
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LogEntry {
     /// Topics associated with the log entry, which identify the event being emitted.
-    #[serde(default)]
-    pub topics: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<B256>,
     /// Data associated with the log entry, typically encoded event data.
-    #[serde(default)]
-    pub data: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
     /// The index of the transaction within the block where this log entry was generated.
     #[serde(default, rename = "transactionIndex")]
     pub transaction_index: u64,
     /// The index of the log entry within the block.
     #[serde(default, rename = "logIndex")]
     pub log_index: u64,
-    #[serde(default)]
-    pub address: String,
-    #[serde(default, rename = "blockNumber")]
-    pub block_number: u64,
-    #[serde(default, rename = "blockHash")]
-    pub block_hash: String,
-    #[serde(default, rename = "transactionHash")]
-    pub transaction_hash: String,
-    #[serde(default)]
-    pub removed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    #[serde(
+        default,
+        rename = "blockNumber",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub block_number: Option<u64>,
+    #[serde(default, rename = "blockHash", skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<B256>,
+    #[serde(
+        default,
+        rename = "transactionHash",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transaction_hash: Option<B256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<bool>,
+    /// The names of the named queries that returned this item, see `DataStream::add_query`.
+    /// Empty for items of the stream's own filters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<String>,
 }
@@ -1,11 +1,24 @@
 //! Structs to represent fetched items
 
-/// Contains a block header along with associated log and transaction entries
+/// Block context attached to items of flattened streams
+pub mod block_context;
+/// Indexed view joining the items of a block to their transactions
+pub mod block_view;
+/// Contains a block header along with associated log, transaction, trace and state diff entries
 pub mod data_item;
 /// Data from fetched logs
 pub mod log_entry;
+/// Data from fetched state diffs
+pub mod state_diff_entry;
+/// Data from fetched traces
+pub mod trace_entry;
 /// Data from fetched transactions
 pub mod transaction_entry;
 
+pub use block_context::{BlockContext, WithBlock};
+pub use block_view::{BlockView, LogView, StateDiffView, TraceView, TransactionView};
+pub use data_item::{BlockHeader, DataItem};
 pub use log_entry::LogEntry;
-pub use transaction_entry::TransactionEntry;
+pub use state_diff_entry::StateDiffEntry;
+pub use trace_entry::TraceEntry;
+pub use transaction_entry::{AccessListItem, TransactionEntry};
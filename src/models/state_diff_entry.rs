@@ -0,0 +1,34 @@
+use crate::filters::state_diff_filter::StateDiffKind;
+use crate::types::Address;
+use serde::{Deserialize, Serialize};
+
+/// Represents a change to an account's balance, nonce, code or storage made by a transaction.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateDiffEntry {
+    /// The index of the transaction within the block that made the change.
+    #[serde(
+        default,
+        rename = "transactionIndex",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transaction_index: Option<u64>,
+    /// The account whose state changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// The storage slot, or one of `balance`, `code` and `nonce`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// The kind of change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<StateDiffKind>,
+    /// The value before the transaction, `None` if it did not exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+    /// The value after the transaction, `None` if it was deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+    /// The names of the named queries that returned this item, see `DataStream::add_query`.
+    /// Empty for items of the stream's own filters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<String>,
+}
@@ -0,0 +1,116 @@
+use crate::filters::trace_filter::TraceType;
+use crate::types::{Address, Bytes, U256};
+use serde::{Deserialize, Serialize};
+
+/// Represents a trace (internal call, contract creation, self-destruct or reward) from a block.
+///
+/// The type-specific data is split between `action` (the inputs) and `result` (the outputs),
+/// and only the fields relevant to the trace type are populated.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TraceEntry {
+    /// The index of the transaction within the block that produced this trace.
+    #[serde(
+        default,
+        rename = "transactionIndex",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub transaction_index: Option<u64>,
+    /// The position of the trace in the call tree, e.g. `[0, 1]` for the second child of the first call.
+    #[serde(
+        default,
+        rename = "traceAddress",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub trace_address: Option<Vec<u64>>,
+    /// The kind of the trace.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub trace_type: Option<TraceType>,
+    /// The number of direct child traces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtraces: Option<u64>,
+    /// The error message if the trace reverted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The decoded revert reason, if any.
+    #[serde(
+        default,
+        rename = "revertReason",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub revert_reason: Option<String>,
+    /// The inputs of the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<TraceAction>,
+    /// The outputs of the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<TraceResult>,
+    /// The names of the named queries that returned this item, see `DataStream::add_query`.
+    /// Empty for items of the stream's own filters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<String>,
+}
+
+/// The inputs of a trace. Which fields are set depends on the trace type.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TraceAction {
+    /// The creator (`create`) or caller (`call`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    /// The callee (`call`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// The amount of wei transferred (`create`, `call`) or rewarded (`reward`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// The gas provided (`create`, `call`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas: Option<U256>,
+    /// The calldata (`call`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<Bytes>,
+    /// The 4-byte function selector (`call`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sighash: Option<Bytes>,
+    /// The call kind, e.g. `call`, `delegatecall` or `staticcall` (`call`).
+    #[serde(default, rename = "callType", skip_serializing_if = "Option::is_none")]
+    pub call_type: Option<String>,
+    /// The init code (`create`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init: Option<Bytes>,
+    /// The self-destructed contract (`suicide`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// The recipient of the remaining balance (`suicide`).
+    #[serde(
+        default,
+        rename = "refundAddress",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub refund_address: Option<Address>,
+    /// The balance sent to the refund address (`suicide`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// The reward recipient (`reward`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<Address>,
+    /// The reward kind, e.g. `block` or `uncle` (`reward`).
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub reward_type: Option<String>,
+}
+
+/// The outputs of a trace. Which fields are set depends on the trace type.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TraceResult {
+    /// The gas used (`create`, `call`).
+    #[serde(default, rename = "gasUsed", skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<U256>,
+    /// The returned data (`call`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    /// The deployed code (`create`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// The address of the created contract (`create`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+}
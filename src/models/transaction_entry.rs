@@ -1,60 +1,192 @@
-use serde::{Deserialize, Deserializer};
+use crate::types::{Address, Bytes, B256, U256};
+use serde::{Deserialize, Serialize};
 
 /// Represents a transaction entry from a block.
 ///
 /// This struct is used to deserialize transaction data, which contains information about
-/// a transaction such as its hash, nonce, and other relevant fields.
-#[derive(Debug, Deserialize, Clone)]
+/// a transaction such as its hash, nonce, and other relevant fields. Receipt data (status, gas
+/// used, created contract) is included alongside the transaction itself.
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransactionEntry {
-    #[serde(default)]
-    pub hash: Option<String>,
-    #[serde(default)]
+    /// The transaction hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<B256>,
+    /// The sender's nonce.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub nonce: Option<u64>,
-    #[serde(default, rename = "transactionIndex")]
+    /// The index of the transaction within the block.
+    #[serde(
+        default,
+        rename = "transactionIndex",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub transaction_index: Option<u64>,
-    #[serde(default)]
-    pub to: Option<String>,
-    // #[serde(default, rename = "blockHash")]
-    // pub block_hash: Option<String>,
-    // #[serde(default, rename = "blockNumber")]
-    // pub block_number: Option<u64>,
-    #[serde(default)]
-    pub from: Option<String>,
-    #[serde(default, deserialize_with = "deserialize_hex_to_u64", rename = "value")]
-    pub value: Option<u64>,
-    #[serde(default, deserialize_with = "deserialize_hex_to_u64", rename = "gas")]
-    pub gas: Option<u64>,
-    #[serde(
-        default,
-        rename = "gasPrice",
-        deserialize_with = "deserialize_hex_to_u64"
-    )]
-    pub gas_price: Option<u64>,
-    #[serde(default)]
-    pub input: Option<String>,
+    /// The recipient, `None` for contract creations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// The hash of the block containing the transaction.
+    #[serde(default, rename = "blockHash", skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<B256>,
+    /// The number of the block containing the transaction.
+    #[serde(
+        default,
+        rename = "blockNumber",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub block_number: Option<u64>,
+    /// The sender.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    /// The amount of wei transferred.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// The gas limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas: Option<U256>,
+    /// The gas price of legacy transactions.
+    #[serde(default, rename = "gasPrice", skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    /// The EIP-1559 maximum total fee per gas.
+    #[serde(
+        default,
+        rename = "maxFeePerGas",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_fee_per_gas: Option<U256>,
+    /// The EIP-1559 maximum priority fee per gas.
+    #[serde(
+        default,
+        rename = "maxPriorityFeePerGas",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_priority_fee_per_gas: Option<U256>,
+    /// The calldata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<Bytes>,
+    /// The first four bytes of the calldata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sighash: Option<Bytes>,
+    /// The transaction type: 0 (legacy), 1 (EIP-2930), 2 (EIP-1559), 3 (EIP-4844), etc.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub tx_type: Option<u64>,
+    /// The receipt status: 1 for success, 0 for failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u64>,
+    /// The gas used by this transaction.
+    #[serde(default, rename = "gasUsed", skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<U256>,
+    /// The gas used by this and all preceding transactions in the block.
+    #[serde(
+        default,
+        rename = "cumulativeGasUsed",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cumulative_gas_used: Option<U256>,
+    /// The price per gas actually paid.
+    #[serde(
+        default,
+        rename = "effectiveGasPrice",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub effective_gas_price: Option<U256>,
+    /// The address of the contract created by this transaction, if any.
+    #[serde(
+        default,
+        rename = "contractAddress",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub contract_address: Option<Address>,
+    /// The chain ID the transaction was signed for.
+    #[serde(default, rename = "chainId", skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    /// The signature's `v` value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v: Option<U256>,
+    /// The signature's `r` value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r: Option<U256>,
+    /// The signature's `s` value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub s: Option<U256>,
+    /// The signature's y-parity for typed transactions.
+    #[serde(default, rename = "yParity", skip_serializing_if = "Option::is_none")]
+    pub y_parity: Option<u64>,
+    /// The EIP-2930 access list.
+    #[serde(
+        default,
+        rename = "accessList",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub access_list: Option<Vec<AccessListItem>>,
+    /// The EIP-4844 maximum fee per blob gas.
+    #[serde(
+        default,
+        rename = "maxFeePerBlobGas",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_fee_per_blob_gas: Option<U256>,
+    /// The EIP-4844 versioned hashes of the blobs carried by the transaction.
+    #[serde(
+        default,
+        rename = "blobVersionedHashes",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub blob_versioned_hashes: Option<Vec<B256>>,
+    /// The names of the named queries that returned this item, see `DataStream::add_query`.
+    /// Empty for items of the stream's own filters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queries: Vec<String>,
 }
 
-/// Deserializes a hexadecimal string into a `u64` value.
-///
-/// This function is used to handle fields that represent numeric values in hex format in JSON.
-///
-/// # Parameters
-///
-/// * `deserializer` - A deserializer instance used to convert JSON data into a `u64` value.
-///
-/// # Returns
-///
-/// * `Result<Option<u64>, D::Error>` - The deserialized `u64` value.
-fn deserialize_hex_to_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    if s.starts_with("0x") {
-        u64::from_str_radix(s.trim_start_matches("0x"), 16)
-            .map(Some)
-            .map_err(serde::de::Error::custom)
-    } else {
-        Ok(None)
+/// An entry of an EIP-2930 access list: an address and the storage slots pre-declared for it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccessListItem {
+    /// The accessed address.
+    pub address: Address,
+    /// The accessed storage slots of the address.
+    #[serde(default, rename = "storageKeys")]
+    pub storage_keys: Vec<B256>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that an EIP-1559 transaction with receipt data deserializes from the worker's wire format.
+    #[test]
+    fn test_eip1559_transaction_deserialization() {
+        let json = r#"{
+            "transactionIndex": 3,
+            "hash": "0xbb4b3fc2b746877dce70862850602f1d19bd890ab4db47e6b7ee1da1fe578a0d",
+            "type": 2,
+            "status": 1,
+            "chainId": 1,
+            "gasUsed": "0x5208",
+            "cumulativeGasUsed": "0x1d8a2",
+            "effectiveGasPrice": "0x1b4d1e8c0",
+            "maxFeePerGas": "0x2540be400",
+            "maxPriorityFeePerGas": "0x3b9aca00",
+            "gasPrice": null,
+            "value": "0x15af1d78b58c40000",
+            "contractAddress": null,
+            "yParity": 1,
+            "sighash": "0xa9059cbb",
+            "accessList": [
+                {
+                    "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000003"]
+                }
+            ]
+        }"#;
+        let tx: TransactionEntry = serde_json::from_str(json).unwrap();
+        assert_eq!(tx.tx_type, Some(2));
+        assert_eq!(tx.status, Some(1));
+        assert_eq!(tx.gas_used, Some(U256::from(21_000u64)));
+        assert_eq!(tx.max_priority_fee_per_gas.unwrap().format_gwei(), "1");
+        assert_eq!(tx.gas_price, None);
+        assert_eq!(tx.value.unwrap().format_ether(), "25");
+        assert_eq!(tx.contract_address, None);
+        assert_eq!(tx.sighash.unwrap().as_slice(), [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(tx.access_list.unwrap()[0].storage_keys.len(), 1);
     }
 }
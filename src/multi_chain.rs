@@ -0,0 +1,389 @@
+use crate::data_source::DataSource;
+use crate::data_stream::DataStream;
+use crate::errors::DataStreamError;
+use crate::models::DataItem;
+use futures::{Stream, StreamExt};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Blocks of one chain delivered by a [`MultiChainStream`].
+#[derive(Debug)]
+pub struct ChainBatch {
+    /// The name the chain was added under.
+    pub chain: String,
+    /// The blocks, in ascending order.
+    pub items: Vec<DataItem>,
+}
+
+/// A batch of a chain, or the error that ended its stream.
+type ChainResult = Result<ChainBatch, DataStreamError>;
+
+/// Drives several [`DataStream`]s, one per chain, as a single stream of [`ChainBatch`]es.
+///
+/// Each chain is streamed in block order. By default a batch is delivered as soon as any chain
+/// has one, so the chains are interleaved in no particular order. With
+/// [`merged_by_timestamp`](MultiChainStream::merged_by_timestamp), blocks of all chains are
+/// delivered one at a time in timestamp order instead, waiting for every chain that has not
+/// ended. Blocks with the same timestamp are delivered in the order the chains were added.
+///
+/// Every chain has its own checkpoint: the last block delivered for it. Resume a chain from
+/// the block after its checkpoint.
+///
+/// # Example
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use subsquid_data_streaming::{DataSource, DataStream, LogFilter, MultiChainStream};
+///
+/// # async fn run() -> Result<(), subsquid_data_streaming::DataStreamError> {
+/// let transfers = || LogFilter::new().with_topic("Transfer(address,address,uint256)");
+/// let mut stream = MultiChainStream::new()
+///     .add_chain(
+///         "ethereum",
+///         DataStream::new()
+///             .set_data_source(DataSource::Subsquid(
+///                 "https://v2.archive.subsquid.io/network/ethereum-mainnet".to_string(),
+///             ))
+///             .from_block(20_000_000)
+///             .add_log_filter(transfers()?),
+///     )
+///     .add_chain(
+///         "base",
+///         DataStream::new()
+///             .set_data_source(DataSource::Subsquid(
+///                 "https://v2.archive.subsquid.io/network/base-mainnet".to_string(),
+///             ))
+///             .from_block(16_000_000)
+///             .add_log_filter(transfers()?),
+///     )
+///     .merged_by_timestamp(true)
+///     .build()
+///     .await?;
+///
+/// while let Some(batch) = stream.next().await {
+///     let batch = batch?;
+///     println!("{}: {} blocks", batch.chain, batch.items.len());
+///     println!("resume {} after {:?}", batch.chain, stream.checkpoint(&batch.chain));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiChainStream {
+    chains: Vec<Chain>,       // The chains, in the order they were added
+    merge_by_timestamp: bool, // Whether blocks are delivered in timestamp order
+    next_chain: usize,        // The chain polled first by the next unmerged poll
+}
+
+/// A chain of a `MultiChainStream` and its delivery state.
+struct Chain {
+    name: String,               // The name the chain was added under
+    stream: DataStream,         // The ordered stream of the chain
+    buffer: VecDeque<DataItem>, // Blocks fetched but not delivered yet, when merging
+    done: bool,                 // Whether the stream has ended
+    checkpoint: Option<u64>,    // The last block delivered
+}
+
+impl MultiChainStream {
+    /// Creates a stream without any chain.
+    pub fn new() -> Self {
+        Self {
+            chains: Vec::new(),
+            merge_by_timestamp: false,
+            next_chain: 0,
+        }
+    }
+
+    /// Adds a chain named `name`, streamed by `stream`.
+    ///
+    /// The stream must not be built yet and needs a `DataSource::Subsquid` data source. It is
+    /// switched to [ordered](DataStream::ordered) delivery when the multi-chain stream is
+    /// built.
+    pub fn add_chain(mut self, name: impl Into<String>, stream: DataStream) -> Self {
+        self.chains.push(Chain {
+            name: name.into(),
+            stream,
+            buffer: VecDeque::new(),
+            done: false,
+            checkpoint: None,
+        });
+        self
+    }
+
+    /// Delivers the blocks of all chains one at a time, in timestamp order.
+    ///
+    /// The block timestamp is selected on every chain. A chain whose next range is slow holds
+    /// back the others.
+    pub fn merged_by_timestamp(mut self, merge: bool) -> Self {
+        self.merge_by_timestamp = merge;
+        self
+    }
+
+    /// Builds the stream of every chain and starts fetching.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` if there is no chain, if two chains
+    /// share a name, or if a chain does not use a `DataSource::Subsquid` data source. Errors
+    /// building the stream of a chain are returned as is.
+    pub async fn build(mut self) -> Result<Self, DataStreamError> {
+        if self.chains.is_empty() {
+            return Err(DataStreamError::ConfigurationError(
+                "a multi-chain stream needs at least one chain".into(),
+            ));
+        }
+        let mut names = HashSet::new();
+        for chain in &self.chains {
+            if !names.insert(chain.name.as_str()) {
+                return Err(DataStreamError::ConfigurationError(format!(
+                    "chain {} was added twice",
+                    chain.name
+                )));
+            }
+            if !matches!(chain.stream.data_source(), Some(DataSource::Subsquid(_))) {
+                return Err(DataStreamError::ConfigurationError(format!(
+                    "chain {} needs a Subsquid data source",
+                    chain.name
+                )));
+            }
+        }
+
+        for chain in &mut self.chains {
+            let mut stream = std::mem::take(&mut chain.stream).ordered(true);
+            if self.merge_by_timestamp {
+                stream.require_block_timestamp();
+            }
+            chain.stream = stream.build().await.inspect_err(|e| {
+                log::error!("Failed to build the stream of chain {}: {}", chain.name, e)
+            })?;
+        }
+        Ok(self)
+    }
+
+    /// Returns the last block delivered for `chain`, or `None` if none has been yet or there
+    /// is no such chain.
+    pub fn checkpoint(&self, chain: &str) -> Option<u64> {
+        self.chains
+            .iter()
+            .find(|c| c.name == chain)
+            .and_then(|c| c.checkpoint)
+    }
+
+    /// Returns the last block delivered for every chain that has delivered one.
+    pub fn checkpoints(&self) -> BTreeMap<String, u64> {
+        self.chains
+            .iter()
+            .filter_map(|c| Some((c.name.clone(), c.checkpoint?)))
+            .collect()
+    }
+
+    /// Delivers the first batch ready on any chain, starting after the chain delivered last.
+    fn poll_interleaved(&mut self, cx: &mut Context<'_>) -> Poll<Option<ChainResult>> {
+        let count = self.chains.len();
+        let mut pending = false;
+        for offset in 0..count {
+            let index = (self.next_chain + offset) % count;
+            let chain = &mut self.chains[index];
+            if chain.done {
+                continue;
+            }
+            match chain.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(items))) => {
+                    self.next_chain = (index + 1) % count;
+                    return Poll::Ready(Some(chain.deliver(items)));
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(chain.fail(e))),
+                Poll::Ready(None) => chain.done = true,
+                Poll::Pending => pending = true,
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(None)
+        }
+    }
+
+    /// Delivers the earliest buffered block once every chain has one buffered or has ended.
+    fn poll_merged(&mut self, cx: &mut Context<'_>) -> Poll<Option<ChainResult>> {
+        let mut pending = false;
+        for chain in &mut self.chains {
+            while chain.buffer.is_empty() && !chain.done {
+                match chain.stream.poll_next_unpin(cx) {
+                    Poll::Ready(Some(Ok(items))) => chain.buffer.extend(items),
+                    Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(chain.fail(e))),
+                    Poll::Ready(None) => chain.done = true,
+                    Poll::Pending => {
+                        pending = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if pending {
+            return Poll::Pending;
+        }
+
+        let earliest = self
+            .chains
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chain)| {
+                let timestamp = chain.buffer.front()?.header.timestamp.unwrap_or_default();
+                Some((timestamp, index))
+            })
+            .min();
+        match earliest {
+            Some((_, index)) => {
+                let chain = &mut self.chains[index];
+                let item = chain.buffer.pop_front().into_iter().collect();
+                Poll::Ready(Some(chain.deliver(item)))
+            }
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl Chain {
+    /// Tags `items` with the chain and moves the checkpoint to the last of them.
+    fn deliver(&mut self, items: Vec<DataItem>) -> ChainResult {
+        if let Some(last) = items.last() {
+            self.checkpoint = Some(last.header.number);
+        }
+        Ok(ChainBatch {
+            chain: self.name.clone(),
+            items,
+        })
+    }
+
+    fn fail(&self, error: DataStreamError) -> ChainResult {
+        log::warn!("Stream of chain {} failed: {}", self.name, error);
+        Err(error)
+    }
+}
+
+impl Default for MultiChainStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stream for MultiChainStream {
+    type Item = ChainResult;
+
+    /// Polls the next batch of any chain, or the next block in timestamp order when merging.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.merge_by_timestamp {
+            this.poll_merged(cx)
+        } else {
+            this.poll_interleaved(cx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, timestamp: u64) -> DataItem {
+        serde_json::from_value(serde_json::json!({
+            "header": { "number": number, "timestamp": timestamp }
+        }))
+        .unwrap()
+    }
+
+    fn multi_chain(chains: Vec<(&str, Vec<Vec<DataItem>>)>, merge: bool) -> MultiChainStream {
+        let mut stream = MultiChainStream::new().merged_by_timestamp(merge);
+        for (name, batches) in chains {
+            let batches = batches.into_iter().map(Ok).collect();
+            stream = stream.add_chain(name, DataStream::from_batches(batches));
+        }
+        stream
+    }
+
+    /// Test that merged blocks come out in timestamp order and move each chain's checkpoint.
+    #[tokio::test]
+    async fn test_merge_by_timestamp() {
+        let mut stream = multi_chain(
+            vec![
+                (
+                    "a",
+                    vec![vec![block(1, 10), block(2, 30)], vec![block(3, 50)]],
+                ),
+                ("b", vec![vec![], vec![block(7, 20), block(8, 30)]]),
+            ],
+            true,
+        );
+
+        let mut delivered = Vec::new();
+        while let Some(batch) = stream.next().await {
+            let batch = batch.unwrap();
+            assert_eq!(batch.items.len(), 1);
+            delivered.push((batch.chain, batch.items[0].header.number));
+            if delivered.len() == 3 {
+                assert_eq!(stream.checkpoint("a"), Some(2));
+                assert_eq!(stream.checkpoint("b"), Some(7));
+            }
+        }
+        let expected = [("a", 1), ("b", 7), ("a", 2), ("b", 8), ("a", 3)];
+        assert_eq!(
+            delivered,
+            expected.map(|(chain, number)| (chain.to_string(), number))
+        );
+        assert_eq!(
+            stream.checkpoints(),
+            BTreeMap::from([("a".to_string(), 3), ("b".to_string(), 8)])
+        );
+    }
+
+    /// Test that unmerged batches are tagged with their chain and alternate between chains.
+    #[tokio::test]
+    async fn test_interleaved_batches() {
+        let mut stream = multi_chain(
+            vec![
+                ("a", vec![vec![block(1, 0)], vec![block(2, 0)]]),
+                ("b", vec![vec![block(5, 0)]]),
+            ],
+            false,
+        );
+
+        let mut delivered = Vec::new();
+        while let Some(batch) = stream.next().await {
+            let batch = batch.unwrap();
+            delivered.push((batch.chain, batch.items.len()));
+        }
+        let expected = [("a", 1), ("b", 1), ("a", 1)];
+        assert_eq!(
+            delivered,
+            expected.map(|(chain, count)| (chain.to_string(), count))
+        );
+        assert_eq!(stream.checkpoint("a"), Some(2));
+        assert_eq!(stream.checkpoint("c"), None);
+    }
+
+    /// Test that chains need distinct names and a Subsquid data source.
+    #[tokio::test]
+    async fn test_build_validation() {
+        assert!(matches!(
+            MultiChainStream::new().build().await,
+            Err(DataStreamError::ConfigurationError(_))
+        ));
+        let source = || DataSource::Subsquid("http://localhost".to_string());
+        let duplicate = MultiChainStream::new()
+            .add_chain("a", DataStream::new().set_data_source(source()))
+            .add_chain("a", DataStream::new().set_data_source(source()));
+        assert!(matches!(
+            duplicate.build().await,
+            Err(DataStreamError::ConfigurationError(_))
+        ));
+        let rpc = MultiChainStream::new().add_chain(
+            "a",
+            DataStream::new().set_data_source(DataSource::EvmRpc("http://localhost".into())),
+        );
+        assert!(matches!(
+            rpc.build().await,
+            Err(DataStreamError::ConfigurationError(_))
+        ));
+    }
+}
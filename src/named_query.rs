@@ -0,0 +1,110 @@
+use crate::fields::{BlockFields, LogFields, StateDiffFields, TraceFields, TransactionFields};
+use crate::filters::{LogFilter, StateDiffFilter, TraceFilter, TransactionFilter};
+use crate::worker_query::QuerySet;
+
+/// A set of filters and field selections fetched under a name by a [`DataStream`].
+///
+/// A stream with several named queries fetches each of them over the same ranges, routed to
+/// the same workers, and merges the results into one batch per range. Every log, transaction,
+/// trace and state diff records in its `queries` field the names of the queries that returned
+/// it, so an item matched by two queries is delivered once with both names. Such an item
+/// carries the fields selected by either query.
+///
+/// [`DataStream`]: crate::DataStream
+///
+/// # Example
+///
+/// ```
+/// use subsquid_data_streaming::{DataStream, LogFields, LogFilter, NamedQuery};
+///
+/// let data_stream = DataStream::new()
+///     .add_query(
+///         NamedQuery::new("transfers")
+///             .add_log_filter(LogFilter::new().with_topic("Transfer(address,address,uint256)")?)
+///             .select_log_fields(LogFields {
+///                 topic0: true,
+///                 data: true,
+///                 ..Default::default()
+///             }),
+///     )
+///     .add_query(
+///         NamedQuery::new("votes")
+///             .add_log_filter(LogFilter::new().with_topic("VoteCast(address,uint256,uint8,uint256,string)")?),
+///     );
+/// # Ok::<(), subsquid_data_streaming::DataStreamError>(())
+/// ```
+#[derive(Clone)]
+pub struct NamedQuery {
+    pub(crate) query: QuerySet, // The filters and field selections, with the name set
+}
+
+impl NamedQuery {
+    /// Creates an empty query named `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            query: QuerySet {
+                name: Some(name.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Returns the name of the query.
+    pub fn name(&self) -> &str {
+        self.query.name.as_deref().unwrap_or_default()
+    }
+
+    /// Adds a filter for logs.
+    pub fn add_log_filter(mut self, filter: LogFilter) -> Self {
+        self.query.log_filters.push(filter);
+        self
+    }
+
+    /// Adds a filter for transactions.
+    pub fn add_tx_filter(mut self, filter: TransactionFilter) -> Self {
+        self.query.tx_filters.push(filter);
+        self
+    }
+
+    /// Adds a filter for traces.
+    pub fn add_trace_filter(mut self, filter: TraceFilter) -> Self {
+        self.query.trace_filters.push(filter);
+        self
+    }
+
+    /// Adds a filter for state diffs.
+    pub fn add_state_diff_filter(mut self, filter: StateDiffFilter) -> Self {
+        self.query.state_diff_filters.push(filter);
+        self
+    }
+
+    /// Selects the block header fields returned by this query.
+    pub fn select_block_fields(mut self, options: BlockFields) -> Self {
+        self.query.block_options = Some(options);
+        self
+    }
+
+    /// Selects the log fields returned by this query.
+    pub fn select_log_fields(mut self, options: LogFields) -> Self {
+        self.query.log_options = Some(options);
+        self
+    }
+
+    /// Selects the transaction fields returned by this query.
+    pub fn select_tx_fields(mut self, options: TransactionFields) -> Self {
+        self.query.tx_options = Some(options);
+        self
+    }
+
+    /// Selects the trace fields returned by this query.
+    pub fn select_trace_fields(mut self, options: TraceFields) -> Self {
+        self.query.trace_options = Some(options);
+        self
+    }
+
+    /// Selects the state diff fields returned by this query.
+    pub fn select_state_diff_fields(mut self, options: StateDiffFields) -> Self {
+        self.query.state_diff_options = Some(options);
+        self
+    }
+}
@@ -0,0 +1,135 @@
+use crate::models::{DataItem, LogEntry, TransactionEntry};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A client-side condition an item must satisfy to be kept.
+pub(crate) type Predicate<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// Client-side filters applied to every batch inside the fetch tasks, before it is queued.
+#[derive(Clone, Default)]
+pub(crate) struct Predicates {
+    pub(crate) logs: Vec<Predicate<LogEntry>>, // All must hold for a log to be kept
+    pub(crate) transactions: Vec<Predicate<TransactionEntry>>, // All must hold for a transaction to be kept
+    pub(crate) stats: FilterStats,                             // Counts of the items dropped so far
+}
+
+impl Predicates {
+    /// Removes the items that fail a predicate from `batch` and counts them.
+    ///
+    /// Blocks left without any item are removed as well, unless `keep_empty_blocks` is set
+    /// (as with `include_all_blocks`). Blocks that were already empty are kept.
+    pub(crate) fn apply(&self, batch: Vec<DataItem>, keep_empty_blocks: bool) -> Vec<DataItem> {
+        if self.logs.is_empty() && self.transactions.is_empty() {
+            return batch;
+        }
+        batch
+            .into_iter()
+            .filter_map(|mut item| {
+                let was_empty = is_empty(&item);
+                if let Some(logs) = &mut item.logs {
+                    let dropped = retain(logs, &self.logs);
+                    self.stats.logs.fetch_add(dropped, Ordering::Relaxed);
+                }
+                if let Some(transactions) = &mut item.transactions {
+                    let dropped = retain(transactions, &self.transactions);
+                    self.stats
+                        .transactions
+                        .fetch_add(dropped, Ordering::Relaxed);
+                }
+                if !keep_empty_blocks && !was_empty && is_empty(&item) {
+                    None
+                } else {
+                    Some(item)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Keeps the items satisfying every predicate and returns how many were removed.
+fn retain<T>(items: &mut Vec<T>, predicates: &[Predicate<T>]) -> u64 {
+    let before = items.len();
+    if !predicates.is_empty() {
+        items.retain(|item| predicates.iter().all(|predicate| predicate(item)));
+    }
+    (before - items.len()) as u64
+}
+
+/// Returns `true` if a block holds no logs, transactions, traces or state diffs.
+fn is_empty(item: &DataItem) -> bool {
+    item.logs.as_ref().is_none_or(Vec::is_empty)
+        && item.transactions.as_ref().is_none_or(Vec::is_empty)
+        && item.traces.as_ref().is_none_or(Vec::is_empty)
+        && item.state_diffs.as_ref().is_none_or(Vec::is_empty)
+}
+
+/// A handle to the number of items dropped by the client-side filters of a `DataStream`.
+///
+/// The counters are shared with the fetch tasks, so a handle obtained before the stream is
+/// consumed keeps reporting up-to-date counts.
+#[derive(Clone, Debug, Default)]
+pub struct FilterStats {
+    logs: Arc<AtomicU64>,
+    transactions: Arc<AtomicU64>,
+}
+
+impl FilterStats {
+    /// Returns the number of logs dropped so far.
+    pub fn dropped_logs(&self) -> u64 {
+        self.logs.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of transactions dropped so far.
+    pub fn dropped_transactions(&self) -> u64 {
+        self.transactions.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::U256;
+
+    fn batch() -> Vec<DataItem> {
+        serde_json::from_value(serde_json::json!([
+            {
+                "header": { "number": 1 },
+                "logs": [{ "logIndex": 0, "data": "0x01" }, { "logIndex": 1, "data": "0x" }],
+                "transactions": [{ "value": "0x64" }, { "value": "0x1" }]
+            },
+            {
+                "header": { "number": 2 },
+                "logs": [{ "logIndex": 0, "data": "0x" }]
+            },
+            { "header": { "number": 3 } }
+        ]))
+        .unwrap()
+    }
+
+    /// Test that failing items are dropped and counted, and emptied blocks are removed.
+    #[test]
+    fn test_apply_predicates() {
+        let predicates = Predicates {
+            logs: vec![Arc::new(|log: &LogEntry| {
+                log.data.as_ref().is_some_and(|data| !data.is_empty())
+            })],
+            transactions: vec![Arc::new(|tx: &TransactionEntry| {
+                tx.value.unwrap_or_default() > U256::from(10u64)
+            })],
+            ..Default::default()
+        };
+        let stats = predicates.stats.clone();
+
+        let kept = predicates.apply(batch(), false);
+        let numbers: Vec<u64> = kept.iter().map(|item| item.header.number).collect();
+        assert_eq!(numbers, vec![1, 3]);
+        assert_eq!(kept[0].logs.as_ref().unwrap().len(), 1);
+        assert_eq!(kept[0].transactions.as_ref().unwrap().len(), 1);
+        assert_eq!(stats.dropped_logs(), 2);
+        assert_eq!(stats.dropped_transactions(), 1);
+
+        let kept = predicates.apply(batch(), true);
+        assert_eq!(kept.len(), 3);
+        assert_eq!(stats.dropped_logs(), 4);
+    }
+}
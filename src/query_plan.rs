@@ -0,0 +1,749 @@
+use crate::models::{DataItem, LogEntry, StateDiffEntry, TraceEntry, TransactionEntry};
+use crate::types::Address;
+use crate::worker_query::QuerySet;
+use crate::{LogFilter, StateDiffFilter, TraceFilter, TransactionFilter};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Splits a query set whose filters hold more than `max_addresses` addresses into several
+/// smaller sets.
+///
+/// Address lists longer than `max_addresses` are cut into chunks, each becoming a filter of its
+/// own with the other conditions unchanged. A filter with several oversized lists is split into
+/// every combination of their chunks, which keeps the union of the sets equal to the original
+/// query. The filters are then packed, in order, into sets of at most `max_addresses`
+/// addresses, except that a filter with more addresses than that across its lists gets a set
+/// of its own. Sets with a raw body are never split.
+pub(crate) fn split_query(query: &QuerySet, max_addresses: usize) -> Vec<QuerySet> {
+    if query.raw_body.is_some() || address_count(query) <= max_addresses {
+        return vec![query.clone()];
+    }
+
+    let mut log_filters = query.log_filters.clone();
+    log_filters = split_by(log_filters, max_addresses, |f: &mut LogFilter| {
+        Some(&mut f.address)
+    });
+
+    let mut tx_filters = query.tx_filters.clone();
+    tx_filters = split_by(tx_filters, max_addresses, |f: &mut TransactionFilter| {
+        f.from.as_mut()
+    });
+    tx_filters = split_by(tx_filters, max_addresses, |f: &mut TransactionFilter| {
+        f.to.as_mut()
+    });
+
+    let mut trace_filters = query.trace_filters.clone();
+    trace_filters = split_by(trace_filters, max_addresses, |f: &mut TraceFilter| {
+        Some(&mut f.create_from)
+    });
+    trace_filters = split_by(trace_filters, max_addresses, |f: &mut TraceFilter| {
+        Some(&mut f.call_from)
+    });
+    trace_filters = split_by(trace_filters, max_addresses, |f: &mut TraceFilter| {
+        Some(&mut f.call_to)
+    });
+    trace_filters = split_by(trace_filters, max_addresses, |f: &mut TraceFilter| {
+        Some(&mut f.suicide_refund_address)
+    });
+    trace_filters = split_by(trace_filters, max_addresses, |f: &mut TraceFilter| {
+        Some(&mut f.reward_author)
+    });
+
+    let mut state_diff_filters = query.state_diff_filters.clone();
+    state_diff_filters = split_by(
+        state_diff_filters,
+        max_addresses,
+        |f: &mut StateDiffFilter| Some(&mut f.address),
+    );
+
+    let empty = QuerySet {
+        log_filters: Vec::new(),
+        tx_filters: Vec::new(),
+        trace_filters: Vec::new(),
+        state_diff_filters: Vec::new(),
+        ..query.clone()
+    };
+    let mut packer = Packer {
+        sets: vec![empty.clone()],
+        empty,
+        weight: 0,
+        max_addresses,
+    };
+    for filter in log_filters {
+        let weight = filter.address.len();
+        packer.take(weight).log_filters.push(filter);
+    }
+    for filter in tx_filters {
+        let weight = tx_addresses(&filter);
+        packer.take(weight).tx_filters.push(filter);
+    }
+    for filter in trace_filters {
+        let weight = trace_addresses(&filter);
+        packer.take(weight).trace_filters.push(filter);
+    }
+    for filter in state_diff_filters {
+        let weight = filter.address.len();
+        packer.take(weight).state_diff_filters.push(filter);
+    }
+    packer.sets
+}
+
+/// Packs filters, in order, into query sets of at most `max_addresses` addresses.
+struct Packer {
+    sets: Vec<QuerySet>,
+    empty: QuerySet,
+    weight: usize,
+    max_addresses: usize,
+}
+
+impl Packer {
+    /// Returns the set a filter with `weight` addresses goes into, starting a new one if the
+    /// current set would grow over the limit.
+    fn take(&mut self, weight: usize) -> &mut QuerySet {
+        if self.weight > 0 && self.weight + weight > self.max_addresses {
+            self.sets.push(self.empty.clone());
+            self.weight = 0;
+        }
+        self.weight += weight;
+        self.sets.last_mut().unwrap()
+    }
+}
+
+/// Normalizes the log and transaction filters of a query set without changing what it matches.
+///
+/// Duplicate addresses and topics are removed, filters that are equal in all but one condition
+/// are merged into one, and filters matched only by items another filter already matches are
+/// dropped. A filter without any condition matches every item of its kind, which is almost
+/// never intended, so a warning is logged for each one.
+pub(crate) fn normalize_query(query: &QuerySet) -> QuerySet {
+    let mut query = query.clone();
+    if query.raw_body.is_some() {
+        return query;
+    }
+
+    for (i, filter) in query.log_filters.iter().enumerate() {
+        if filter.address.is_empty() && filter.topic0.is_empty() {
+            log::warn!("Log filter {} has no conditions and matches every log", i);
+        }
+    }
+    for (i, filter) in query.tx_filters.iter().enumerate() {
+        if filter.from.is_none() && filter.to.is_none() {
+            log::warn!(
+                "Transaction filter {} has no conditions and matches every transaction",
+                i
+            );
+        }
+    }
+
+    let log_filters = query
+        .log_filters
+        .iter()
+        .map(|filter| Conditions {
+            a: (!filter.address.is_empty()).then(|| filter.address.iter().copied().collect()),
+            b: (!filter.topic0.is_empty()).then(|| filter.topic0.iter().copied().collect()),
+        })
+        .collect();
+    query.log_filters = normalize_conditions(log_filters)
+        .into_iter()
+        .map(|conditions| LogFilter {
+            address: conditions.a.unwrap_or_default().into_iter().collect(),
+            topic0: conditions.b.unwrap_or_default().into_iter().collect(),
+        })
+        .collect();
+
+    let tx_filters = query
+        .tx_filters
+        .iter()
+        .map(|filter| Conditions {
+            a: filter
+                .from
+                .as_ref()
+                .map(|from| from.iter().copied().collect()),
+            b: filter.to.as_ref().map(|to| to.iter().copied().collect()),
+        })
+        .collect();
+    query.tx_filters = normalize_conditions(tx_filters)
+        .into_iter()
+        .map(|conditions| TransactionFilter {
+            from: conditions.a.map(|from| from.into_iter().collect()),
+            to: conditions.b.map(|to| to.into_iter().collect()),
+        })
+        .collect();
+
+    query
+}
+
+/// A filter of two conditions that must both hold. A condition is a set of accepted values,
+/// or `None` to accept any value.
+struct Conditions<A, B> {
+    a: Option<BTreeSet<A>>,
+    b: Option<BTreeSet<B>>,
+}
+
+impl<A: Ord, B: Ord> Conditions<A, B> {
+    /// Returns whether every item matched by `self` is also matched by `other`.
+    fn is_subsumed_by(&self, other: &Self) -> bool {
+        condition_within(&self.a, &other.a) && condition_within(&self.b, &other.b)
+    }
+}
+
+/// Merges filters that share a condition and drops subsumed ones, until nothing changes.
+fn normalize_conditions<A, B>(mut filters: Vec<Conditions<A, B>>) -> Vec<Conditions<A, B>>
+where
+    A: Ord + Clone,
+    B: Ord + Clone,
+{
+    loop {
+        let count = filters.len();
+
+        // Filters with the same `b` merge into one accepting the union of their `a`, and
+        // the other way around.
+        let mut by_b: BTreeMap<Option<BTreeSet<B>>, Option<BTreeSet<A>>> = BTreeMap::new();
+        for filter in filters {
+            match by_b.get_mut(&filter.b) {
+                Some(a) => *a = union(a.take(), filter.a),
+                None => {
+                    by_b.insert(filter.b, filter.a);
+                }
+            }
+        }
+        let mut by_a: BTreeMap<Option<BTreeSet<A>>, Option<BTreeSet<B>>> = BTreeMap::new();
+        for (b, a) in by_b {
+            match by_a.get_mut(&a) {
+                Some(merged) => *merged = union(merged.take(), b),
+                None => {
+                    by_a.insert(a, b);
+                }
+            }
+        }
+        filters = by_a.into_iter().map(|(a, b)| Conditions { a, b }).collect();
+
+        // After merging no two filters are equal, so subsumption is strict.
+        let subsumed: Vec<bool> = filters
+            .iter()
+            .enumerate()
+            .map(|(i, filter)| {
+                filters
+                    .iter()
+                    .enumerate()
+                    .any(|(j, other)| i != j && filter.is_subsumed_by(other))
+            })
+            .collect();
+        let mut subsumed = subsumed.into_iter();
+        filters.retain(|_| !subsumed.next().unwrap_or(false));
+
+        if filters.len() == count {
+            return filters;
+        }
+    }
+}
+
+/// Returns whether every value accepted by `narrow` is accepted by `wide`.
+fn condition_within<T: Ord>(narrow: &Option<BTreeSet<T>>, wide: &Option<BTreeSet<T>>) -> bool {
+    match (narrow, wide) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(narrow), Some(wide)) => narrow.is_subset(wide),
+    }
+}
+
+/// Returns a condition accepting the values of both `x` and `y`.
+fn union<T: Ord>(x: Option<BTreeSet<T>>, y: Option<BTreeSet<T>>) -> Option<BTreeSet<T>> {
+    match (x, y) {
+        (Some(mut x), Some(y)) => {
+            x.extend(y);
+            Some(x)
+        }
+        _ => None,
+    }
+}
+
+/// Merges the batches fetched by the sub-queries of one range into a single batch.
+///
+/// Blocks are ordered by number and the items of each block are deduplicated, so that an item
+/// matched by several sub-queries is delivered once. Items are identified by the indices the
+/// archive always returns with them: the log index of logs, the transaction index of
+/// transactions, the transaction index and trace address of traces, and the transaction index,
+/// address and key of state diffs. When sub-queries selected different fields, the merged item
+/// carries the fields of all of them, along with all their query names. Items are sorted by
+/// their position in the block.
+pub(crate) fn merge_batches(batches: Vec<Vec<DataItem>>) -> Vec<DataItem> {
+    let mut blocks: BTreeMap<u64, DataItem> = BTreeMap::new();
+    for item in batches.into_iter().flatten() {
+        match blocks.get_mut(&item.header.number) {
+            Some(merged) => {
+                fill_fields(&mut merged.header, &item.header);
+                merge_items(&mut merged.logs, item.logs);
+                merge_items(&mut merged.transactions, item.transactions);
+                merge_items(&mut merged.traces, item.traces);
+                merge_items(&mut merged.state_diffs, item.state_diffs);
+            }
+            None => {
+                blocks.insert(item.header.number, item);
+            }
+        }
+    }
+
+    blocks
+        .into_values()
+        .map(|mut item| {
+            if let Some(logs) = &mut item.logs {
+                logs.sort_by_key(|log| log.log_index);
+            }
+            if let Some(transactions) = &mut item.transactions {
+                transactions.sort_by_key(|tx| tx.transaction_index);
+            }
+            if let Some(traces) = &mut item.traces {
+                traces.sort_by(|a, b| {
+                    (a.transaction_index, &a.trace_address)
+                        .cmp(&(b.transaction_index, &b.trace_address))
+                });
+            }
+            if let Some(state_diffs) = &mut item.state_diffs {
+                state_diffs.sort_by_key(|diff| diff.transaction_index);
+            }
+            item
+        })
+        .collect()
+}
+
+/// Returns the sub-queries fetched for every range: those of the stream's own filters, and
+/// those of each named query.
+///
+/// The stream's own query is left out when named queries are given and it has no filters of
+/// its own, since it would fetch nothing. Named queries share the stream's patches.
+pub(crate) fn plan_queries(
+    query: &QuerySet,
+    named: &[QuerySet],
+    max_addresses: usize,
+) -> Vec<QuerySet> {
+    let mut query_sets = Vec::new();
+    if named.is_empty()
+        || has_filters(query)
+        || query.include_all_blocks
+        || query.raw_body.is_some()
+    {
+        query_sets.extend(split_query(&normalize_query(query), max_addresses));
+    }
+    for named in named {
+        let named = QuerySet {
+            patches: query.patches.clone(),
+            ..named.clone()
+        };
+        query_sets.extend(split_query(&normalize_query(&named), max_addresses));
+    }
+    if query_sets.len() > 1 {
+        log::debug!(
+            "Fetching each range with {} queries of at most {} addresses",
+            query_sets.len(),
+            max_addresses
+        );
+    }
+    query_sets
+}
+
+/// Records `name` as a query returning every item of `batch`.
+pub(crate) fn tag_batch(batch: &mut [DataItem], name: &str) {
+    for item in batch {
+        tag_items(&mut item.logs, name);
+        tag_items(&mut item.transactions, name);
+        tag_items(&mut item.traces, name);
+        tag_items(&mut item.state_diffs, name);
+    }
+}
+
+/// Returns `true` if a query set has at least one filter of any kind.
+pub(crate) fn has_filters(query: &QuerySet) -> bool {
+    !(query.log_filters.is_empty()
+        && query.tx_filters.is_empty()
+        && query.trace_filters.is_empty()
+        && query.state_diff_filters.is_empty())
+}
+
+/// Returns the number of addresses in the filters of a query set.
+pub(crate) fn address_count(query: &QuerySet) -> usize {
+    query
+        .log_filters
+        .iter()
+        .map(|f| f.address.len())
+        .chain(query.tx_filters.iter().map(tx_addresses))
+        .chain(query.trace_filters.iter().map(trace_addresses))
+        .chain(query.state_diff_filters.iter().map(|f| f.address.len()))
+        .sum()
+}
+
+fn tx_addresses(filter: &TransactionFilter) -> usize {
+    filter.from.as_ref().map_or(0, Vec::len) + filter.to.as_ref().map_or(0, Vec::len)
+}
+
+fn trace_addresses(filter: &TraceFilter) -> usize {
+    filter.create_from.len()
+        + filter.call_from.len()
+        + filter.call_to.len()
+        + filter.suicide_refund_address.len()
+        + filter.reward_author.len()
+}
+
+/// Replaces every filter whose `list` holds more than `max` addresses with one filter per
+/// chunk of `max` addresses.
+fn split_by<F, L>(filters: Vec<F>, max: usize, list: L) -> Vec<F>
+where
+    F: Clone,
+    L: Fn(&mut F) -> Option<&mut Vec<Address>>,
+{
+    let mut split = Vec::with_capacity(filters.len());
+    for mut filter in filters {
+        let addresses = match list(&mut filter) {
+            Some(addresses) if addresses.len() > max => std::mem::take(addresses),
+            _ => {
+                split.push(filter);
+                continue;
+            }
+        };
+        for chunk in addresses.chunks(max) {
+            let mut part = filter.clone();
+            if let Some(addresses) = list(&mut part) {
+                *addresses = chunk.to_vec();
+            }
+            split.push(part);
+        }
+    }
+    split
+}
+
+/// An item of a block that can be identified and tagged with query names.
+trait BlockItem: Serialize + DeserializeOwned {
+    /// Returns a key identifying the item within its block.
+    fn key(&self) -> String;
+    /// Returns the names of the queries that returned the item.
+    fn queries(&mut self) -> &mut Vec<String>;
+}
+
+impl BlockItem for LogEntry {
+    fn key(&self) -> String {
+        self.log_index.to_string()
+    }
+
+    fn queries(&mut self) -> &mut Vec<String> {
+        &mut self.queries
+    }
+}
+
+impl BlockItem for TransactionEntry {
+    fn key(&self) -> String {
+        format!("{:?}", self.transaction_index)
+    }
+
+    fn queries(&mut self) -> &mut Vec<String> {
+        &mut self.queries
+    }
+}
+
+impl BlockItem for TraceEntry {
+    fn key(&self) -> String {
+        format!("{:?}/{:?}", self.transaction_index, self.trace_address)
+    }
+
+    fn queries(&mut self) -> &mut Vec<String> {
+        &mut self.queries
+    }
+}
+
+impl BlockItem for StateDiffEntry {
+    fn key(&self) -> String {
+        format!(
+            "{:?}/{:?}/{:?}",
+            self.transaction_index, self.address, self.key
+        )
+    }
+
+    fn queries(&mut self) -> &mut Vec<String> {
+        &mut self.queries
+    }
+}
+
+/// Adds `items` to `merged`, merging the items both hold.
+fn merge_items<T: BlockItem>(merged: &mut Option<Vec<T>>, items: Option<Vec<T>>) {
+    let Some(items) = items else {
+        return;
+    };
+    let merged = merged.get_or_insert_with(Vec::new);
+    let mut index: HashMap<String, usize> = merged
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (item.key(), i))
+        .collect();
+    for mut item in items {
+        match index.get(&item.key()) {
+            Some(&i) => {
+                let existing = &mut merged[i];
+                fill_fields(existing, &item);
+                for name in std::mem::take(item.queries()) {
+                    if !existing.queries().contains(&name) {
+                        existing.queries().push(name);
+                    }
+                }
+            }
+            None => {
+                index.insert(item.key(), merged.len());
+                merged.push(item);
+            }
+        }
+    }
+}
+
+/// Copies into `into` the fields that only `other` holds, such as fields selected by one
+/// sub-query but not by another.
+fn fill_fields<T: Serialize + DeserializeOwned>(into: &mut T, other: &T) {
+    let (Ok(Value::Object(mut fields)), Ok(Value::Object(other))) =
+        (serde_json::to_value(&*into), serde_json::to_value(other))
+    else {
+        return;
+    };
+    let count = fields.len();
+    for (key, value) in other {
+        fields.entry(key).or_insert(value);
+    }
+    if fields.len() > count {
+        if let Ok(filled) = serde_json::from_value(Value::Object(fields)) {
+            *into = filled;
+        }
+    }
+}
+
+fn tag_items<T: BlockItem>(items: &mut Option<Vec<T>>, name: &str) {
+    for item in items.iter_mut().flatten() {
+        if !item.queries().iter().any(|query| query == name) {
+            item.queries().push(name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::B256;
+    use std::collections::HashSet;
+
+    /// Test that oversized lists are chunked and packed into sets under the limit.
+    #[test]
+    fn test_split_query() {
+        let query = QuerySet {
+            log_filters: vec![LogFilter {
+                address: (0..5).map(Address::from_low_u64).collect(),
+                topic0: Vec::new(),
+            }],
+            tx_filters: vec![TransactionFilter::new().add_from(Address::from_low_u64(10))],
+            ..Default::default()
+        };
+
+        let sets = split_query(&query, 2);
+        let logs: Vec<Vec<Vec<Address>>> = sets
+            .iter()
+            .map(|set| set.log_filters.iter().map(|f| f.address.clone()).collect())
+            .collect();
+        assert_eq!(
+            logs,
+            vec![
+                vec![vec![Address::from_low_u64(0), Address::from_low_u64(1)]],
+                vec![vec![Address::from_low_u64(2), Address::from_low_u64(3)]],
+                vec![vec![Address::from_low_u64(4)]],
+            ]
+        );
+        assert!(sets[..2].iter().all(|set| set.tx_filters.is_empty()));
+        assert_eq!(sets[2].tx_filters.len(), 1);
+        assert!(sets.iter().all(|set| address_count(set) <= 2));
+
+        assert_eq!(split_query(&query, 6).len(), 1);
+    }
+
+    /// Test that a filter with two oversized lists is split into every combination of chunks.
+    #[test]
+    fn test_split_query_cross_product() {
+        let query = QuerySet {
+            tx_filters: vec![TransactionFilter {
+                from: Some((0..4).map(Address::from_low_u64).collect()),
+                to: Some((4..8).map(Address::from_low_u64).collect()),
+            }],
+            ..Default::default()
+        };
+        let sets = split_query(&query, 2);
+        assert_eq!(sets.len(), 4);
+        let pairs: HashSet<(Vec<Address>, Vec<Address>)> = sets
+            .iter()
+            .map(|set| {
+                let filter = &set.tx_filters[0];
+                (filter.from.clone().unwrap(), filter.to.clone().unwrap())
+            })
+            .collect();
+        assert!(pairs.contains(&(
+            vec![Address::from_low_u64(2), Address::from_low_u64(3)],
+            vec![Address::from_low_u64(4), Address::from_low_u64(5)]
+        )));
+    }
+
+    /// Test that duplicates are removed, compatible filters merged and subsumed ones dropped.
+    #[test]
+    fn test_normalize_query() {
+        let topic = |n: u64| -> B256 { format!("0x{:064x}", n).parse().unwrap() };
+        let log_filter = |addresses: &[u64], topics: &[u64]| LogFilter {
+            address: addresses
+                .iter()
+                .copied()
+                .map(Address::from_low_u64)
+                .collect(),
+            topic0: topics.iter().copied().map(topic).collect(),
+        };
+        let query = QuerySet {
+            log_filters: vec![
+                log_filter(&[1, 1, 2], &[7]),
+                log_filter(&[3], &[7]),
+                // Subsumed by the merged filter above
+                log_filter(&[2], &[7]),
+                log_filter(&[9], &[]),
+                // Subsumed by the filter on address 9 with any topic
+                log_filter(&[9], &[8]),
+            ],
+            tx_filters: vec![
+                TransactionFilter::new().add_from(Address::from_low_u64(1)),
+                TransactionFilter::new()
+                    .add_from(Address::from_low_u64(1))
+                    .add_to(Address::from_low_u64(2)),
+                TransactionFilter::new().add_from(Address::from_low_u64(3)),
+            ],
+            ..Default::default()
+        };
+
+        let normalized = normalize_query(&query);
+        let logs: Vec<(Vec<Address>, Vec<B256>)> = normalized
+            .log_filters
+            .iter()
+            .map(|f| (f.address.clone(), f.topic0.clone()))
+            .collect();
+        assert_eq!(
+            logs,
+            vec![
+                (
+                    vec![
+                        Address::from_low_u64(1),
+                        Address::from_low_u64(2),
+                        Address::from_low_u64(3)
+                    ],
+                    vec![topic(7)]
+                ),
+                (vec![Address::from_low_u64(9)], vec![]),
+            ]
+        );
+        assert_eq!(normalized.tx_filters.len(), 1);
+        assert_eq!(
+            normalized.tx_filters[0].from,
+            Some(vec![Address::from_low_u64(1), Address::from_low_u64(3)])
+        );
+        assert_eq!(normalized.tx_filters[0].to, None);
+    }
+
+    /// Test that an empty filter absorbs every other filter of its kind.
+    #[test]
+    fn test_normalize_query_empty_filter() {
+        let query = QuerySet {
+            log_filters: vec![
+                LogFilter::new().add_address(Address::from_low_u64(1)),
+                LogFilter::new(),
+            ],
+            ..Default::default()
+        };
+        let normalized = normalize_query(&query);
+        assert_eq!(normalized.log_filters.len(), 1);
+        assert!(normalized.log_filters[0].address.is_empty());
+    }
+
+    /// Test that sub-query batches merge into one ordered, deduplicated batch.
+    #[test]
+    fn test_merge_batches() {
+        let block = |number: u64, logs: &[u64]| -> DataItem {
+            serde_json::from_value(serde_json::json!({
+                "header": { "number": number },
+                "logs": logs.iter().map(|index| serde_json::json!({
+                    "topics": [],
+                    "data": "0x",
+                    "transactionIndex": 0,
+                    "logIndex": index,
+                    "address": "0xdac17f958d2ee523a2206206994597c13d831ec7",
+                    "blockNumber": number,
+                    "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "removed": false
+                })).collect::<Vec<_>>()
+            }))
+            .unwrap()
+        };
+
+        let merged = merge_batches(vec![
+            vec![block(1, &[2]), block(3, &[0])],
+            vec![block(1, &[0, 2]), block(2, &[5])],
+        ]);
+        let blocks: Vec<(u64, Vec<u64>)> = merged
+            .iter()
+            .map(|item| {
+                let logs = item.logs.as_ref().unwrap();
+                (
+                    item.header.number,
+                    logs.iter().map(|l| l.log_index).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(blocks, vec![(1, vec![0, 2]), (2, vec![5]), (3, vec![0])]);
+    }
+
+    /// Test that an item returned by two named queries keeps both names and both field sets.
+    #[test]
+    fn test_merge_named_batches() {
+        let block = |transaction: serde_json::Value| -> Vec<DataItem> {
+            vec![serde_json::from_value(serde_json::json!({
+                "header": { "number": 1 },
+                "transactions": [transaction]
+            }))
+            .unwrap()]
+        };
+        let mut swaps =
+            block(serde_json::json!({ "transactionIndex": 4, "hash": format!("0x{:064x}", 1) }));
+        let mut transfers = block(serde_json::json!({ "transactionIndex": 4, "status": 1 }));
+        let mut other = block(serde_json::json!({ "transactionIndex": 2, "status": 0 }));
+        tag_batch(&mut swaps, "swaps");
+        tag_batch(&mut transfers, "transfers");
+        tag_batch(&mut other, "transfers");
+
+        let merged = merge_batches(vec![swaps, transfers, other]);
+        let transactions = merged[0].transactions.as_ref().unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].transaction_index, Some(2));
+        assert_eq!(transactions[0].queries, vec!["transfers"]);
+        assert_eq!(transactions[1].queries, vec!["swaps", "transfers"]);
+        assert!(transactions[1].hash.is_some());
+        assert_eq!(transactions[1].status, Some(1));
+    }
+
+    /// Test that named queries are planned after the stream's own filters, which are skipped
+    /// when empty.
+    #[test]
+    fn test_plan_queries() {
+        let named = QuerySet {
+            name: Some("transfers".to_string()),
+            log_filters: vec![LogFilter::new().add_address(Address::from_low_u64(1))],
+            ..Default::default()
+        };
+        let plan = plan_queries(&QuerySet::default(), std::slice::from_ref(&named), 10);
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].name.as_deref(), Some("transfers"));
+
+        let query = QuerySet {
+            tx_filters: vec![TransactionFilter::new().add_to(Address::from_low_u64(2))],
+            ..Default::default()
+        };
+        let plan = plan_queries(&query, &[named], 10);
+        let names: Vec<Option<&str>> = plan.iter().map(|set| set.name.as_deref()).collect();
+        assert_eq!(names, vec![None, Some("transfers")]);
+    }
+}
@@ -0,0 +1,313 @@
+use crate::data_source::DataSource;
+use crate::errors::DataStreamError;
+use crate::fields::{BlockFields, LogFields, StateDiffFields, TraceFields, TransactionFields};
+use crate::filters::{LogFilter, StateDiffFilter, TraceFilter, TransactionFilter};
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+
+/// A declarative description of a `DataStream`, loadable from TOML, YAML or JSON.
+///
+/// Keys use the Rust field names of the filter and field types. Unknown keys are rejected so
+/// that typos do not silently widen a query.
+///
+/// ```toml
+/// [source]
+/// subsquid = "https://v2.archive.subsquid.io/network/ethereum-mainnet"
+///
+/// [range]
+/// from = 20000000
+/// to = 20010000
+///
+/// [[logs]]
+/// address = ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]
+/// topic0 = ["Transfer(address,address,uint256)"]
+///
+/// [fields.log]
+/// topic0 = true
+/// data = true
+///
+/// [tuning]
+/// chunk_size = 5000
+/// max_concurrency = 8
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamSpec {
+    /// Where to fetch data from.
+    pub source: DataSource,
+    /// The block range to stream.
+    #[serde(default)]
+    pub range: RangeSpec,
+    /// Log filters.
+    #[serde(default)]
+    pub logs: Vec<LogFilter>,
+    /// Transaction filters.
+    #[serde(default)]
+    pub transactions: Vec<TransactionFilter>,
+    /// Trace filters.
+    #[serde(default)]
+    pub traces: Vec<TraceFilter>,
+    /// State diff filters.
+    #[serde(default)]
+    pub state_diffs: Vec<StateDiffFilter>,
+    /// The fields to select for each kind of item.
+    #[serde(default)]
+    pub fields: FieldsSpec,
+    /// Performance settings.
+    #[serde(default)]
+    pub tuning: TuningSpec,
+}
+
+/// The block range of a [`StreamSpec`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RangeSpec {
+    /// The first block to stream.
+    pub from: u64,
+    /// The last block to stream, or `None` to stream up to the dataset height.
+    pub to: Option<u64>,
+    /// Whether to return blocks without matching items.
+    pub include_all_blocks: bool,
+}
+
+/// The field selection of a [`StreamSpec`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldsSpec {
+    pub block: Option<BlockFields>,
+    pub log: Option<LogFields>,
+    pub transaction: Option<TransactionFields>,
+    pub trace: Option<TraceFields>,
+    pub state_diff: Option<StateDiffFields>,
+}
+
+/// The performance settings of a [`StreamSpec`]. Unset values keep the library defaults.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TuningSpec {
+    /// The number of blocks per worker query range.
+    pub chunk_size: Option<u64>,
+    /// The maximum number of ranges fetched at the same time.
+    pub max_concurrency: Option<usize>,
+    /// The maximum number of filter addresses sent in one worker query.
+    pub max_addresses_per_query: Option<usize>,
+}
+
+impl StreamSpec {
+    /// Parses a spec from a TOML document.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` prefixed with the path of the offending
+    /// key, e.g. `logs[0].address[1]: ...`.
+    pub fn from_toml_str(document: &str) -> Result<Self, DataStreamError> {
+        let deserializer = toml::Deserializer::parse(document)
+            .map_err(|e| DataStreamError::ConfigurationError(format!("invalid TOML: {}", e)))?;
+        deserialize(deserializer)
+    }
+
+    /// Parses a spec from a YAML document.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` prefixed with the path of the offending
+    /// key.
+    pub fn from_yaml_str(document: &str) -> Result<Self, DataStreamError> {
+        deserialize(serde_norway::Deserializer::from_str(document))
+    }
+
+    /// Parses a spec from a JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` prefixed with the path of the offending
+    /// key.
+    pub fn from_json_str(document: &str) -> Result<Self, DataStreamError> {
+        deserialize(&mut serde_json::Deserializer::from_str(document))
+    }
+
+    /// Reads a spec from a file, choosing the format from its `.toml`, `.yaml`, `.yml` or
+    /// `.json` extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` if the file cannot be read, has an
+    /// unknown extension or does not contain a valid spec.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, DataStreamError> {
+        let path = path.as_ref();
+        let document = std::fs::read_to_string(path).map_err(|e| {
+            DataStreamError::ConfigurationError(format!("cannot read {}: {}", path.display(), e))
+        })?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml_str(&document),
+            Some("yaml" | "yml") => Self::from_yaml_str(&document),
+            Some("json") => Self::from_json_str(&document),
+            _ => Err(DataStreamError::ConfigurationError(format!(
+                "cannot tell the format of {}: expected a .toml, .yaml, .yml or .json file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Checks the constraints that cannot be expressed by the document structure.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` naming the offending key.
+    pub fn validate(&self) -> Result<(), DataStreamError> {
+        let invalid = |key: &str, reason: String| {
+            Err(DataStreamError::ConfigurationError(format!(
+                "{}: {}",
+                key, reason
+            )))
+        };
+        if let Some(to) = self.range.to {
+            if to < self.range.from {
+                return invalid(
+                    "range.to",
+                    format!("{} is below range.from ({})", to, self.range.from),
+                );
+            }
+        }
+        if self.tuning.chunk_size == Some(0) {
+            return invalid("tuning.chunk_size", "must be at least 1".to_string());
+        }
+        if self.tuning.max_concurrency == Some(0) {
+            return invalid("tuning.max_concurrency", "must be at least 1".to_string());
+        }
+        if self.tuning.max_addresses_per_query == Some(0) {
+            return invalid(
+                "tuning.max_addresses_per_query",
+                "must be at least 1".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes a spec, reporting errors with the path of the offending key.
+fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StreamSpec, DataStreamError> {
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let message = e.inner().to_string();
+        // YAML errors already start with the path.
+        if message.starts_with(&format!("{}: ", path)) {
+            DataStreamError::ConfigurationError(message)
+        } else {
+            DataStreamError::ConfigurationError(format!("{}: {}", path, message))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::keccak256;
+
+    /// Test that the same spec parses from TOML, YAML and JSON.
+    #[test]
+    fn test_spec_formats() {
+        let toml = r#"
+            [source]
+            subsquid = "https://v2.archive.subsquid.io/network/ethereum-mainnet"
+
+            [range]
+            from = 100
+            to = 200
+
+            [[logs]]
+            address = ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]
+            topic0 = ["Transfer(address,address,uint256)"]
+
+            [[transactions]]
+            to = ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]
+
+            [fields.log]
+            data = true
+
+            [tuning]
+            chunk_size = 50
+        "#;
+        let yaml = r#"
+            source:
+              subsquid: https://v2.archive.subsquid.io/network/ethereum-mainnet
+            range: { from: 100, to: 200 }
+            logs:
+              - address: ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]
+                topic0: ["Transfer(address,address,uint256)"]
+            transactions:
+              - to: ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]
+            fields:
+              log: { data: true }
+            tuning: { chunk_size: 50 }
+        "#;
+        let json = r#"{
+            "source": { "subsquid": "https://v2.archive.subsquid.io/network/ethereum-mainnet" },
+            "range": { "from": 100, "to": 200 },
+            "logs": [{
+                "address": ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"],
+                "topic0": ["Transfer(address,address,uint256)"]
+            }],
+            "transactions": [{ "to": ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"] }],
+            "fields": { "log": { "data": true } },
+            "tuning": { "chunk_size": 50 }
+        }"#;
+
+        for spec in [
+            StreamSpec::from_toml_str(toml).unwrap(),
+            StreamSpec::from_yaml_str(yaml).unwrap(),
+            StreamSpec::from_json_str(json).unwrap(),
+        ] {
+            assert!(matches!(spec.source, DataSource::Subsquid(_)));
+            assert_eq!((spec.range.from, spec.range.to), (100, Some(200)));
+            assert_eq!(
+                spec.logs[0].topic0,
+                vec![keccak256("Transfer(address,address,uint256)")]
+            );
+            assert_eq!(spec.transactions[0].to.as_ref().unwrap().len(), 1);
+            assert!(spec.fields.log.as_ref().unwrap().data);
+            assert!(spec.fields.transaction.is_none());
+            assert_eq!(spec.tuning.chunk_size, Some(50));
+            assert_eq!(spec.tuning.max_concurrency, None);
+        }
+    }
+
+    /// Test that errors point to the offending key.
+    #[test]
+    fn test_spec_error_paths() {
+        let error = |result: Result<StreamSpec, DataStreamError>| match result {
+            Err(DataStreamError::ConfigurationError(message)) => message,
+            other => panic!(
+                "expected a configuration error, got {:?}",
+                other.map(|_| ())
+            ),
+        };
+
+        let message = error(StreamSpec::from_yaml_str(
+            r#"
+            source: { subsquid: "https://example.com" }
+            logs:
+              - address: ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", "0x1234"]
+            "#,
+        ));
+        assert!(message.starts_with("logs[0].address[1]: "), "{}", message);
+        assert_eq!(message.matches("logs[0]").count(), 1, "{}", message);
+
+        let message = error(StreamSpec::from_json_str(
+            r#"{ "source": { "subsquid": "https://example.com" }, "fields": { "log": { "topics": true } } }"#,
+        ));
+        assert!(
+            message.starts_with("fields.log.topics: unknown field `topics`"),
+            "{}",
+            message
+        );
+
+        let message = error(
+            StreamSpec::from_toml_str(
+                "source = { subsquid = \"https://example.com\" }\nrange = { from = 10, to = 5 }\n",
+            )
+            .and_then(|spec| spec.validate().map(|_| spec)),
+        );
+        assert!(message.starts_with("range.to: "), "{}", message);
+    }
+}
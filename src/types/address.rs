@@ -0,0 +1,192 @@
+use super::{decode_fixed_hex, encode_hex, impl_hex_serde, keccak256};
+use crate::errors::DataStreamError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A 20-byte EVM account address.
+///
+/// Parsing accepts all-lowercase and all-uppercase hex, and mixed-case hex only if it is a valid
+/// EIP-55 checksum, so that typos in checksummed addresses are caught. `Display` produces the
+/// checksummed form, while serialization (used in queries) produces lowercase hex.
+///
+/// # Example
+///
+/// ```
+/// use subsquid_data_streaming::Address;
+///
+/// let usdc: Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".parse().unwrap();
+/// assert_eq!(usdc.to_string(), "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+/// assert!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB49".parse::<Address>().is_err());
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address([u8; 20]);
+
+impl Address {
+    /// The zero address.
+    pub const ZERO: Address = Address([0; 20]);
+
+    /// Creates an address from a slice, failing unless it is exactly 20 bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, DataStreamError> {
+        bytes.try_into().map(Address).map_err(|_| {
+            DataStreamError::ValidationError(format!(
+                "invalid address: expected 20 bytes, got {}",
+                bytes.len()
+            ))
+        })
+    }
+
+    /// Creates an address whose low eight bytes are `n` in big-endian order, for tests.
+    #[cfg(test)]
+    pub(crate) fn from_low_u64(n: u64) -> Self {
+        let mut bytes = [0; 20];
+        bytes[12..].copy_from_slice(&n.to_be_bytes());
+        Address(bytes)
+    }
+
+    /// Returns the raw bytes of the address.
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Returns the EIP-55 checksummed representation of the address.
+    pub fn to_checksum(&self) -> String {
+        let lower = encode_hex(&self.0);
+        let hash = keccak256(&lower.as_bytes()[2..]);
+        let mut checksummed = String::with_capacity(42);
+        checksummed.push_str("0x");
+        for (i, c) in lower[2..].chars().enumerate() {
+            let nibble = (hash.as_bytes()[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                checksummed.push(c.to_ascii_uppercase());
+            } else {
+                checksummed.push(c);
+            }
+        }
+        checksummed
+    }
+}
+
+impl FromStr for Address {
+    type Err = DataStreamError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = Address(decode_fixed_hex(s, "address")?);
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        let has_lower = digits.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = digits.chars().any(|c| c.is_ascii_uppercase());
+        if has_lower && has_upper && address.to_checksum()[2..] != *digits {
+            return Err(DataStreamError::ValidationError(format!(
+                "invalid address {:?}: EIP-55 checksum mismatch",
+                s
+            )));
+        }
+        Ok(address)
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Address(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Address {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Address {
+    /// Formats the address with its EIP-55 checksum.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+impl fmt::LowerHex for Address {
+    /// Formats the address as lowercase hex with the `0x` prefix.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_hex(&self.0))
+    }
+}
+
+impl_hex_serde!(Address, "a 20-byte hex address");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the checksum vectors from EIP-55.
+    #[test]
+    fn test_eip55_checksums() {
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let address: Address = checksummed.to_lowercase().parse().unwrap();
+            assert_eq!(address.to_string(), checksummed);
+            assert_eq!(checksummed.parse::<Address>().unwrap(), address);
+            assert_eq!(
+                checksummed
+                    .to_uppercase()
+                    .replace("0X", "0x")
+                    .parse::<Address>()
+                    .unwrap(),
+                address
+            );
+            assert_eq!(
+                checksummed
+                    .replacen("0x", "0X", 1)
+                    .parse::<Address>()
+                    .unwrap(),
+                address
+            );
+            assert_eq!(
+                checksummed
+                    .to_lowercase()
+                    .replacen("0x", "0X", 1)
+                    .parse::<Address>()
+                    .unwrap(),
+                address
+            );
+        }
+    }
+
+    /// Test that malformed addresses are rejected with a validation error.
+    #[test]
+    fn test_invalid_addresses() {
+        for invalid in [
+            "0xabcd",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaeg",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed00",
+        ] {
+            assert!(matches!(
+                invalid.parse::<Address>(),
+                Err(DataStreamError::ValidationError(_))
+            ));
+        }
+    }
+
+    /// Test that addresses are serialized as lowercase hex for queries.
+    #[test]
+    fn test_serde() {
+        let address: Address =
+            serde_json::from_str("\"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&address).unwrap(),
+            "\"0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed\""
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use super::{decode_fixed_hex, encode_hex, impl_hex_serde, U256};
+use crate::errors::DataStreamError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A fixed 32-byte value, such as a block or transaction hash, a log topic or a storage slot.
+///
+/// `Display` and serialization both produce lowercase, `0x`-prefixed hex.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct B256([u8; 32]);
+
+impl B256 {
+    /// The all-zero value.
+    pub const ZERO: B256 = B256([0; 32]);
+
+    /// Creates a value from a slice, failing unless it is exactly 32 bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, DataStreamError> {
+        bytes.try_into().map(B256).map_err(|_| {
+            DataStreamError::ValidationError(format!(
+                "invalid 32-byte value: expected 32 bytes, got {}",
+                bytes.len()
+            ))
+        })
+    }
+
+    /// Returns the raw bytes of the value.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl FromStr for B256 {
+    type Err = DataStreamError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode_fixed_hex(s, "32-byte value").map(B256)
+    }
+}
+
+impl From<[u8; 32]> for B256 {
+    fn from(bytes: [u8; 32]) -> Self {
+        B256(bytes)
+    }
+}
+
+impl From<U256> for B256 {
+    /// Converts a number to its 32-byte big-endian representation.
+    fn from(value: U256) -> Self {
+        B256(value.to_be_bytes())
+    }
+}
+
+impl From<B256> for U256 {
+    /// Interprets the bytes as a big-endian number.
+    fn from(value: B256) -> Self {
+        U256::from_be_bytes(value.0)
+    }
+}
+
+impl AsRef<[u8]> for B256 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for B256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_hex(&self.0))
+    }
+}
+
+impl fmt::Debug for B256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_hex(&self.0))
+    }
+}
+
+impl_hex_serde!(B256, "a 32-byte hex value");
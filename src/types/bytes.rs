@@ -0,0 +1,97 @@
+use super::{decode_hex, encode_hex, impl_hex_serde};
+use crate::errors::DataStreamError;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// An arbitrary-length byte string, such as calldata, log data or contract code.
+///
+/// `Display` and serialization both produce lowercase, `0x`-prefixed hex; `"0x"` is the empty string.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bytes(Vec<u8>);
+
+impl Bytes {
+    /// Creates a byte string from a vector.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+
+    /// Returns the bytes as a slice.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Consumes the byte string and returns the underlying vector.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl FromStr for Bytes {
+    type Err = DataStreamError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode_hex(s, "byte string").map(Bytes)
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_hex(&self.0))
+    }
+}
+
+impl fmt::Debug for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_hex(&self.0))
+    }
+}
+
+impl_hex_serde!(Bytes, "a hex byte string");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::B256;
+
+    /// Test parsing and formatting of byte strings and 32-byte values.
+    #[test]
+    fn test_hex_round_trip() {
+        let calldata: Bytes = "0xa9059cbb".parse().unwrap();
+        assert_eq!(calldata.as_slice(), [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(calldata.to_string(), "0xa9059cbb");
+        assert!("0x".parse::<Bytes>().unwrap().is_empty());
+        assert!("0xabc".parse::<Bytes>().is_err());
+
+        let hash = "0xBB4B3FC2B746877DCE70862850602F1D19BD890AB4DB47E6B7EE1DA1FE578A0D";
+        let parsed: B256 = hash.parse().unwrap();
+        assert_eq!(parsed.to_string(), hash.to_lowercase());
+        assert!("0xbb4b".parse::<B256>().is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Primitive EVM types used by the models and filters
+
+/// 20-byte account addresses with EIP-55 checksums
+pub mod address;
+/// Fixed 32-byte values such as hashes and topics
+pub mod b256;
+/// Arbitrary-length byte strings such as calldata and log data
+pub mod bytes;
+/// Unsigned 256-bit integers for wei amounts, gas and other quantities
+pub mod u256;
+
+pub use address::Address;
+pub use b256::B256;
+pub use bytes::Bytes;
+pub use u256::U256;
+
+use crate::errors::DataStreamError;
+use tiny_keccak::{Hasher, Keccak};
+
+/// Computes the Keccak-256 hash of `data`, as used for event topics, function selectors and
+/// address checksums.
+///
+/// # Example
+///
+/// ```
+/// use subsquid_data_streaming::types::keccak256;
+///
+/// let topic0 = keccak256("Transfer(address,address,uint256)");
+/// assert_eq!(
+///     topic0.to_string(),
+///     "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+/// );
+/// ```
+pub fn keccak256(data: impl AsRef<[u8]>) -> B256 {
+    let mut hasher = Keccak::v256();
+    let mut output = [0u8; 32];
+    hasher.update(data.as_ref());
+    hasher.finalize(&mut output);
+    B256::from(output)
+}
+
+/// Decodes a hex string, with or without the `0x` prefix, into bytes.
+///
+/// `kind` names the expected value in the validation error, e.g. `"address"`.
+pub(crate) fn decode_hex(input: &str, kind: &str) -> Result<Vec<u8>, DataStreamError> {
+    let digits = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .unwrap_or(input);
+    if !digits.len().is_multiple_of(2) {
+        return Err(DataStreamError::ValidationError(format!(
+            "invalid {} {:?}: odd number of hex digits",
+            kind, input
+        )));
+    }
+    digits
+        .as_bytes()
+        .chunks_exact(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16);
+            let low = (pair[1] as char).to_digit(16);
+            match (high, low) {
+                (Some(high), Some(low)) => Ok((high * 16 + low) as u8),
+                _ => Err(DataStreamError::ValidationError(format!(
+                    "invalid {} {:?}: invalid hex digit",
+                    kind, input
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Decodes a hex string into exactly `N` bytes.
+pub(crate) fn decode_fixed_hex<const N: usize>(
+    input: &str,
+    kind: &str,
+) -> Result<[u8; N], DataStreamError> {
+    let bytes = decode_hex(input, kind)?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        DataStreamError::ValidationError(format!(
+            "invalid {} {:?}: expected {} bytes, got {}",
+            kind,
+            input,
+            N,
+            bytes.len()
+        ))
+    })
+}
+
+/// Encodes bytes as a lowercase, `0x`-prefixed hex string.
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(2 + bytes.len() * 2);
+    output.push_str("0x");
+    for byte in bytes {
+        output.push_str(&format!("{:02x}", byte));
+    }
+    output
+}
+
+/// Implements `Serialize` as a hex string and `Deserialize` through `FromStr` for a hex type.
+macro_rules! impl_hex_serde {
+    ($type:ty, $expecting:literal) => {
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&$crate::types::encode_hex(self.as_ref()))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct HexVisitor;
+
+                impl serde::de::Visitor<'_> for HexVisitor {
+                    type Value = $type;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str($expecting)
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<$type, E> {
+                        value.parse().map_err(E::custom)
+                    }
+                }
+
+                deserializer.deserialize_str(HexVisitor)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_hex_serde;
@@ -0,0 +1,691 @@
+use crate::errors::DataStreamError;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, BitAnd, BitOr, Div, Mul, Not, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
+
+/// Number of decimals of gwei relative to wei.
+pub const GWEI_DECIMALS: u32 = 9;
+/// Number of decimals of ether relative to wei.
+pub const ETHER_DECIMALS: u32 = 18;
+
+/// An unsigned 256-bit integer, used for wei amounts, gas prices and other EVM quantities.
+///
+/// Values are stored as four 64-bit limbs, least significant first. Arithmetic operators panic on
+/// overflow, division by zero or underflow; use the `checked_*` methods to handle those cases.
+///
+/// On the wire, quantities are hex strings (`"0x1bc16d674ec80000"`). Deserialization also accepts
+/// decimal strings and JSON numbers, and serialization always produces the hex form.
+///
+/// # Example
+///
+/// ```
+/// use subsquid_data_streaming::U256;
+///
+/// let value: U256 = "0x1bc16d674ec80000".parse().unwrap();
+/// assert_eq!(value.to_string(), "2000000000000000000");
+/// assert_eq!(value.format_ether(), "2");
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    /// The value `0`.
+    pub const ZERO: U256 = U256([0; 4]);
+    /// The value `1`.
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    /// The largest representable value, `2^256 - 1`.
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    /// Creates a value from its little-endian 64-bit limbs.
+    pub const fn from_limbs(limbs: [u64; 4]) -> Self {
+        U256(limbs)
+    }
+
+    /// Returns the little-endian 64-bit limbs of the value.
+    pub const fn as_limbs(&self) -> &[u64; 4] {
+        &self.0
+    }
+
+    /// Creates a value from 32 big-endian bytes, as found in ABI-encoded words and storage slots.
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, chunk) in bytes.chunks_exact(8).enumerate() {
+            limbs[3 - i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    /// Returns the value as 32 big-endian bytes.
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().rev().enumerate() {
+            bytes[i * 8..(i + 1) * 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Parses a hex quantity with or without the `0x` prefix. `"0x"` is read as zero.
+    pub fn from_hex_str(s: &str) -> Result<Self, DataStreamError> {
+        let digits = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        let digits = digits.trim_start_matches('0');
+        if digits.len() > 64 {
+            return Err(invalid(s, "value exceeds 256 bits"));
+        }
+        let mut value = U256::ZERO;
+        for c in digits.chars() {
+            let digit = c
+                .to_digit(16)
+                .ok_or_else(|| invalid(s, "invalid hex digit"))?;
+            value = (value << 4) | U256::from(digit as u64);
+        }
+        Ok(value)
+    }
+
+    /// Parses a decimal integer.
+    pub fn from_dec_str(s: &str) -> Result<Self, DataStreamError> {
+        if s.is_empty() {
+            return Err(invalid(s, "empty string"));
+        }
+        let ten = U256::from(10u64);
+        let mut value = U256::ZERO;
+        for c in s.chars() {
+            let digit = c
+                .to_digit(10)
+                .ok_or_else(|| invalid(s, "invalid decimal digit"))?;
+            value = value
+                .checked_mul(ten)
+                .and_then(|v| v.checked_add(U256::from(digit as u64)))
+                .ok_or_else(|| invalid(s, "value exceeds 256 bits"))?;
+        }
+        Ok(value)
+    }
+
+    /// Parses a decimal amount expressed in units with `decimals` decimals, e.g. `"1.5"` ether.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::types::u256::{U256, ETHER_DECIMALS};
+    ///
+    /// let wei = U256::parse_units("1.5", ETHER_DECIMALS).unwrap();
+    /// assert_eq!(wei, U256::from(1_500_000_000_000_000_000u64));
+    /// ```
+    pub fn parse_units(s: &str, decimals: u32) -> Result<Self, DataStreamError> {
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        if frac.len() > decimals as usize {
+            return Err(invalid(s, "too many decimal places"));
+        }
+        let digits = format!("{}{:0<width$}", int, frac, width = decimals as usize);
+        U256::from_dec_str(&digits)
+    }
+
+    /// Returns `true` if the value is zero.
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Returns the number of significant bits.
+    pub fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return i as u32 * 64 + (64 - self.0[i].leading_zeros());
+            }
+        }
+        0
+    }
+
+    /// Returns the bit at position `index`, counting from the least significant bit.
+    pub fn bit(&self, index: u32) -> bool {
+        index < 256 && (self.0[(index / 64) as usize] >> (index % 64)) & 1 == 1
+    }
+
+    /// Returns the value as a `u64` if it fits.
+    pub fn to_u64(&self) -> Option<u64> {
+        (self.0[1..] == [0; 3]).then_some(self.0[0])
+    }
+
+    /// Returns the value as a `u128` if it fits.
+    pub fn to_u128(&self) -> Option<u128> {
+        (self.0[2..] == [0; 2]).then_some(((self.0[1] as u128) << 64) | self.0[0] as u128)
+    }
+
+    /// Adds `rhs`, returning the wrapped result and whether an overflow occurred.
+    pub fn overflowing_add(self, rhs: U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (U256(result), carry)
+    }
+
+    /// Subtracts `rhs`, returning the wrapped result and whether an underflow occurred.
+    pub fn overflowing_sub(self, rhs: U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (U256(result), borrow)
+    }
+
+    /// Multiplies by `rhs`, returning the wrapped result and whether an overflow occurred.
+    pub fn overflowing_mul(self, rhs: U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut overflow = false;
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let product = self.0[i] as u128 * rhs.0[j] as u128 + carry;
+                if i + j < 4 {
+                    let sum = result[i + j] as u128 + (product as u64) as u128;
+                    result[i + j] = sum as u64;
+                    carry = (product >> 64) + (sum >> 64);
+                } else if product != 0 {
+                    overflow = true;
+                    carry = product >> 64;
+                }
+            }
+            if carry != 0 {
+                overflow = true;
+            }
+        }
+        (U256(result), overflow)
+    }
+
+    /// Returns `self + rhs`, or `None` on overflow.
+    pub fn checked_add(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_add(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns `self - rhs`, or `None` on underflow.
+    pub fn checked_sub(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_sub(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns `self * rhs`, or `None` on overflow.
+    pub fn checked_mul(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_mul(rhs) {
+            (value, false) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns `self / rhs`, or `None` if `rhs` is zero.
+    pub fn checked_div(self, rhs: U256) -> Option<U256> {
+        self.checked_div_rem(rhs).map(|(quotient, _)| quotient)
+    }
+
+    /// Returns `self % rhs`, or `None` if `rhs` is zero.
+    pub fn checked_rem(self, rhs: U256) -> Option<U256> {
+        self.checked_div_rem(rhs).map(|(_, remainder)| remainder)
+    }
+
+    /// Returns `self + rhs`, clamped to `U256::MAX`.
+    pub fn saturating_add(self, rhs: U256) -> U256 {
+        self.checked_add(rhs).unwrap_or(U256::MAX)
+    }
+
+    /// Returns `self - rhs`, clamped to zero.
+    pub fn saturating_sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).unwrap_or(U256::ZERO)
+    }
+
+    /// Returns `self + rhs` modulo `2^256`.
+    pub fn wrapping_add(self, rhs: U256) -> U256 {
+        self.overflowing_add(rhs).0
+    }
+
+    /// Returns `self - rhs` modulo `2^256`.
+    pub fn wrapping_sub(self, rhs: U256) -> U256 {
+        self.overflowing_sub(rhs).0
+    }
+
+    /// Returns the quotient and remainder of `self / rhs`, or `None` if `rhs` is zero.
+    pub fn checked_div_rem(self, rhs: U256) -> Option<(U256, U256)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        if self < rhs {
+            return Some((U256::ZERO, self));
+        }
+        if let Some(divisor) = rhs.to_u64() {
+            let (quotient, remainder) = self.div_rem_u64(divisor);
+            return Some((quotient, U256::from(remainder)));
+        }
+
+        // Shift-subtract long division. `remainder` stays below `rhs`, so when shifting it left
+        // drops a bit, the true value exceeds `rhs` and the wrapped subtraction is exact.
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for i in (0..self.bits()).rev() {
+            let carry = remainder.bit(255);
+            remainder = remainder << 1;
+            if self.bit(i) {
+                remainder.0[0] |= 1;
+            }
+            if carry || remainder >= rhs {
+                remainder = remainder.wrapping_sub(rhs);
+                quotient.0[(i / 64) as usize] |= 1 << (i % 64);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    /// Divides by a non-zero `u64`, returning the quotient and remainder.
+    fn div_rem_u64(self, divisor: u64) -> (U256, u64) {
+        let mut quotient = [0u64; 4];
+        let mut remainder = 0u128;
+        for i in (0..4).rev() {
+            let current = (remainder << 64) | self.0[i] as u128;
+            quotient[i] = (current / divisor as u128) as u64;
+            remainder = current % divisor as u128;
+        }
+        (U256(quotient), remainder as u64)
+    }
+
+    /// Formats the value as a decimal amount with `decimals` decimals, trimming trailing zeros.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use subsquid_data_streaming::U256;
+    ///
+    /// assert_eq!(U256::from(1_234_500u64).format_units(6), "1.2345");
+    /// ```
+    pub fn format_units(&self, decimals: u32) -> String {
+        let digits = self.to_string();
+        let decimals = decimals as usize;
+        if decimals == 0 {
+            return digits;
+        }
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (int, frac) = padded.split_at(padded.len() - decimals);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            int.to_string()
+        } else {
+            format!("{}.{}", int, frac)
+        }
+    }
+
+    /// Formats the value as an amount of wei, i.e. a plain decimal integer.
+    pub fn format_wei(&self) -> String {
+        self.to_string()
+    }
+
+    /// Formats a wei amount in gwei.
+    pub fn format_gwei(&self) -> String {
+        self.format_units(GWEI_DECIMALS)
+    }
+
+    /// Formats a wei amount in ether.
+    pub fn format_ether(&self) -> String {
+        self.format_units(ETHER_DECIMALS)
+    }
+}
+
+/// Builds the validation error returned when `input` cannot be parsed as a `U256`.
+fn invalid(input: &str, reason: &str) -> DataStreamError {
+    DataStreamError::ValidationError(format!("invalid U256 {:?}: {}", input, reason))
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+impl From<u32> for U256 {
+    fn from(value: u32) -> Self {
+        U256::from(value as u64)
+    }
+}
+
+impl From<bool> for U256 {
+    fn from(value: bool) -> Self {
+        U256::from(value as u64)
+    }
+}
+
+impl FromStr for U256 {
+    type Err = DataStreamError;
+
+    /// Parses a `0x`-prefixed hex quantity or a decimal integer.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") || s.starts_with("0X") {
+            U256::from_hex_str(s)
+        } else {
+            U256::from_dec_str(s)
+        }
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for U256 {
+    type Output = U256;
+
+    fn add(self, rhs: U256) -> U256 {
+        self.checked_add(rhs).expect("U256 addition overflowed")
+    }
+}
+
+impl Sub for U256 {
+    type Output = U256;
+
+    fn sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).expect("U256 subtraction underflowed")
+    }
+}
+
+impl Mul for U256 {
+    type Output = U256;
+
+    fn mul(self, rhs: U256) -> U256 {
+        self.checked_mul(rhs)
+            .expect("U256 multiplication overflowed")
+    }
+}
+
+impl Div for U256 {
+    type Output = U256;
+
+    fn div(self, rhs: U256) -> U256 {
+        self.checked_div(rhs).expect("U256 division by zero")
+    }
+}
+
+impl Rem for U256 {
+    type Output = U256;
+
+    fn rem(self, rhs: U256) -> U256 {
+        self.checked_rem(rhs).expect("U256 remainder by zero")
+    }
+}
+
+impl Not for U256 {
+    type Output = U256;
+
+    fn not(self) -> U256 {
+        U256(self.0.map(|limb| !limb))
+    }
+}
+
+impl BitAnd for U256 {
+    type Output = U256;
+
+    fn bitand(self, rhs: U256) -> U256 {
+        U256([
+            self.0[0] & rhs.0[0],
+            self.0[1] & rhs.0[1],
+            self.0[2] & rhs.0[2],
+            self.0[3] & rhs.0[3],
+        ])
+    }
+}
+
+impl BitOr for U256 {
+    type Output = U256;
+
+    fn bitor(self, rhs: U256) -> U256 {
+        U256([
+            self.0[0] | rhs.0[0],
+            self.0[1] | rhs.0[1],
+            self.0[2] | rhs.0[2],
+            self.0[3] | rhs.0[3],
+        ])
+    }
+}
+
+impl Shl<u32> for U256 {
+    type Output = U256;
+
+    /// Shifts left, discarding bits shifted past the 256th. Shifts of 256 or more yield zero.
+    fn shl(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut result = [0u64; 4];
+        for (i, limb) in result.iter_mut().enumerate().skip(limb_shift) {
+            *limb = self.0[i - limb_shift] << bit_shift;
+            if bit_shift > 0 && i > limb_shift {
+                *limb |= self.0[i - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        U256(result)
+    }
+}
+
+impl Shr<u32> for U256 {
+    type Output = U256;
+
+    /// Shifts right. Shifts of 256 or more yield zero.
+    fn shr(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let limb_shift = (shift / 64) as usize;
+        let bit_shift = shift % 64;
+        let mut result = [0u64; 4];
+        for (i, limb) in result.iter_mut().take(4 - limb_shift).enumerate() {
+            *limb = self.0[i + limb_shift] >> bit_shift;
+            if bit_shift > 0 && i + limb_shift + 1 < 4 {
+                *limb |= self.0[i + limb_shift + 1] << (64 - bit_shift);
+            }
+        }
+        U256(result)
+    }
+}
+
+impl fmt::Display for U256 {
+    /// Formats the value as a decimal integer.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        // Peel off 19 decimal digits at a time, the most that fit in a u64.
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        let mut rest = *self;
+        while !rest.is_zero() {
+            let (quotient, remainder) = rest.div_rem_u64(CHUNK);
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        let mut digits = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:019}", chunk));
+        }
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    /// Formats the value as hex without leading zeros; `{:#x}` adds the `0x` prefix.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digits = String::new();
+        for limb in self.0.iter().rev() {
+            if digits.is_empty() {
+                if *limb != 0 {
+                    digits = format!("{:x}", limb);
+                }
+            } else {
+                digits.push_str(&format!("{:016x}", limb));
+            }
+        }
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        f.pad_integral(true, "0x", &digits)
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#x}", self))
+    }
+}
+
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct U256Visitor;
+
+        impl Visitor<'_> for U256Visitor {
+            type Value = U256;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a hex quantity, a decimal string or a non-negative integer")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<U256, E> {
+                value.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<U256, E> {
+                Ok(U256::from(value))
+            }
+
+            fn visit_u128<E: de::Error>(self, value: u128) -> Result<U256, E> {
+                Ok(U256::from(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<U256, E> {
+                u64::try_from(value)
+                    .map(U256::from)
+                    .map_err(|_| E::custom(format!("negative quantity {}", value)))
+            }
+        }
+
+        deserializer.deserialize_any(U256Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test hex and decimal parsing, including values beyond `u64`.
+    #[test]
+    fn test_parsing() {
+        let max = "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+        assert_eq!(max.parse::<U256>().unwrap(), U256::MAX);
+        assert_eq!(
+            U256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(U256::MAX.to_string().parse::<U256>().unwrap(), U256::MAX);
+        assert_eq!("0x0".parse::<U256>().unwrap(), U256::ZERO);
+        assert_eq!(U256::from_hex_str("0x").unwrap(), U256::ZERO);
+        assert!("0x1g".parse::<U256>().is_err());
+        assert!(format!("{}0", U256::MAX).parse::<U256>().is_err());
+        assert!(U256::from_hex_str(&format!("{}0", max)).is_err());
+    }
+
+    /// Test arithmetic across limb boundaries.
+    #[test]
+    fn test_arithmetic() {
+        let a = U256::from(u64::MAX);
+        let b = a + U256::ONE;
+        assert_eq!(b, U256::from_limbs([0, 1, 0, 0]));
+        assert_eq!(b - U256::ONE, a);
+        assert_eq!(a * a, U256::from(u64::MAX as u128 * u64::MAX as u128));
+        assert_eq!((a * a) / a, a);
+        assert_eq!(U256::MAX / U256::MAX, U256::ONE);
+        assert_eq!((U256::MAX - U256::ONE) % U256::MAX, U256::MAX - U256::ONE);
+        let big = U256::from_limbs([7, 0, 0, 1]);
+        let divisor = U256::from_limbs([0, 3, 0, 0]);
+        let (quotient, remainder) = big.checked_div_rem(divisor).unwrap();
+        assert_eq!(quotient * divisor + remainder, big);
+        assert!(remainder < divisor);
+        assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+        assert_eq!(U256::ZERO.checked_sub(U256::ONE), None);
+        assert_eq!(U256::MAX.checked_mul(U256::from(2u64)), None);
+        assert_eq!(U256::ONE.checked_div(U256::ZERO), None);
+        assert_eq!((U256::ONE << 255) >> 255, U256::ONE);
+        assert_eq!(U256::ONE << 256, U256::ZERO);
+    }
+
+    /// Test formatting in wei, gwei and ether.
+    #[test]
+    fn test_unit_formatting() {
+        let value = U256::from(25_000_000_000_000_000_000u128);
+        assert_eq!(value.format_wei(), "25000000000000000000");
+        assert_eq!(value.format_gwei(), "25000000000");
+        assert_eq!(value.format_ether(), "25");
+        assert_eq!(U256::from(1u64).format_ether(), "0.000000000000000001");
+        assert_eq!(U256::from(1_500_000_000u64).format_gwei(), "1.5");
+        assert_eq!(U256::ZERO.format_ether(), "0");
+        assert_eq!(U256::parse_units("25", ETHER_DECIMALS).unwrap(), value);
+        assert!(U256::parse_units("0.0000000001", GWEI_DECIMALS).is_err());
+        assert_eq!(format!("{:#x}", U256::from(255u64)), "0xff");
+        assert_eq!(
+            format!("{:x}", U256::from_limbs([1, 1, 0, 0])),
+            "10000000000000001"
+        );
+    }
+
+    /// Test that quantities round-trip through serde in their wire format.
+    #[test]
+    fn test_serde() {
+        let value: U256 = serde_json::from_str("\"0x15af1d78b58c40000\"").unwrap();
+        assert_eq!(value.format_ether(), "25");
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            "\"0x15af1d78b58c40000\""
+        );
+        assert_eq!(
+            serde_json::from_str::<U256>("21000").unwrap(),
+            U256::from(21_000u64)
+        );
+        assert_eq!(
+            serde_json::from_str::<U256>("\"21000\"").unwrap(),
+            U256::from(21_000u64)
+        );
+        assert!(serde_json::from_str::<U256>("-1").is_err());
+        let missing: Option<U256> = serde_json::from_str("null").unwrap();
+        assert_eq!(missing, None);
+    }
+}
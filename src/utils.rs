@@ -17,3 +17,28 @@ pub fn parse_block_range(
 
     ranges
 }
+
+/// Splits the same range as `parse_block_range` into chunks walked backward, so the first
+/// chunk ends at the last block and every chunk but the last one is full.
+pub fn parse_block_range_reverse(
+    from: u64,
+    to: Option<u64>,
+    chunk_size: u64,
+    max_block: u64,
+) -> Vec<(u64, u64)> {
+    let to_block = to.unwrap_or(max_block);
+
+    let mut ranges = Vec::new();
+    let mut end = to_block;
+
+    while end >= from {
+        let start = end.saturating_sub(chunk_size - 1).max(from);
+        ranges.push((start, end));
+        if start == 0 {
+            break;
+        }
+        end = start - 1;
+    }
+
+    ranges
+}
@@ -1,6 +1,5 @@
 use crate::errors::DataStreamError;
 use crate::models::data_item::DataItem;
-use crate::worker_query::WorkerQuery;
 use reqwest::Client;
 
 /// `WorkerClient` is responsible for sending the `WorkerQuery` to the worker node and fetching the corresponding data.
@@ -28,11 +27,11 @@ impl WorkerClient {
         }
     }
 
-    /// Sends a `WorkerQuery` to the worker node and fetches the data matching the query.
+    /// Sends a query to the worker node and fetches the data matching the query.
     ///
     /// # Arguments
     ///
-    /// * `query` - The query specifying the block range, filters, and field options.
+    /// * `query` - The JSON body of the query, specifying the block range, filters, and field options.
     ///
     /// # Returns
     ///
@@ -43,7 +42,7 @@ impl WorkerClient {
     /// Returns a `DataStreamError` if there is an issue with the request or response deserialization.
     pub(crate) async fn fetch_data(
         &self,
-        query: &WorkerQuery,
+        query: &serde_json::Value,
     ) -> Result<Vec<DataItem>, DataStreamError> {
         let resp = self.client.post(&self.base_url).json(query).send().await?;
         let status = resp.status();
@@ -1,15 +1,83 @@
-use std::collections::HashMap;
-
+use crate::errors::DataStreamError;
 use crate::fields::fields::Fields;
 use crate::filters::log_filter::LogsFilter;
+use crate::filters::state_diff_filter::StateDiffsFilter;
+use crate::filters::trace_filter::TracesFilter;
 use crate::filters::transaction_filter::TransactionsFilter;
-use crate::{LogFields, LogFilter, TransactionFields, TransactionFilter};
+use crate::{
+    BlockFields, LogFields, LogFilter, StateDiffFields, StateDiffFilter, TraceFields, TraceFilter,
+    TransactionFields, TransactionFilter,
+};
 use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// A user-supplied edit of the JSON body of every worker query.
+pub(crate) type QueryPatch = Arc<dyn Fn(&mut Value) + Send + Sync>;
+
+/// The filters and field selections that make up the body of a worker query.
+///
+/// A `QuerySet` is independent of the block range, so the same set can be reused for every
+/// range the stream is split into.
+#[derive(Clone, Default)]
+pub(crate) struct QuerySet {
+    pub(crate) log_filters: Vec<LogFilter>, // Filters for logs to be streamed
+    pub(crate) tx_filters: Vec<TransactionFilter>, // Filters for transactions to be streamed
+    pub(crate) trace_filters: Vec<TraceFilter>, // Filters for traces to be streamed
+    pub(crate) state_diff_filters: Vec<StateDiffFilter>, // Filters for state diffs to be streamed
+    pub(crate) block_options: Option<BlockFields>, // Fields to select for block headers
+    pub(crate) log_options: Option<LogFields>, // Fields to select for logs
+    pub(crate) tx_options: Option<TransactionFields>, // Fields to select for transactions
+    pub(crate) trace_options: Option<TraceFields>, // Fields to select for traces
+    pub(crate) state_diff_options: Option<StateDiffFields>, // Fields to select for state diffs
+    pub(crate) include_all_blocks: bool,    // Whether to return blocks without matching items
+    pub(crate) raw_body: Option<Value>,     // Replaces the body built from the filters and fields
+    pub(crate) patches: Vec<QueryPatch>,    // Edits applied to the body of every query, in order
+    pub(crate) name: Option<String>,        // The name tagged on items of a named query
+}
+
+impl QuerySet {
+    /// Builds the exact JSON body sent to a worker for the given range.
+    ///
+    /// The body is the raw body if one was supplied, or the query built from the filters and
+    /// fields otherwise. Patches are applied next, and `fromBlock` and `toBlock` are set last so
+    /// that the range always matches the one being fetched.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `DataStreamError::ConfigurationError` if the body is not a JSON object.
+    pub(crate) fn body(
+        &self,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> Result<Value, DataStreamError> {
+        let mut body = match &self.raw_body {
+            Some(body) => body.clone(),
+            None => serde_json::to_value(WorkerQuery::from_filters(from_block, to_block, self))?,
+        };
+        for patch in &self.patches {
+            patch(&mut body);
+        }
+
+        let Some(object) = body.as_object_mut() else {
+            return Err(DataStreamError::ConfigurationError(format!(
+                "the worker query body must be a JSON object, got {}",
+                body
+            )));
+        };
+        object.insert("fromBlock".to_string(), Value::from(from_block));
+        match to_block {
+            Some(to_block) => object.insert("toBlock".to_string(), Value::from(to_block)),
+            None => object.remove("toBlock"),
+        };
+        Ok(body)
+    }
+}
 
 /// Represents a query to be sent to the worker node, specifying the block range and filtering criteria.
 ///
-/// The `WorkerQuery` defines the range of blocks to fetch and optional filters for logs and transactions.
-/// The fields define which data to retrieve for each log and transaction (topics, data, etc.).
+/// The `WorkerQuery` defines the range of blocks to fetch and optional filters for logs, transactions, traces and state diffs.
+/// The fields define which data to retrieve for each log, transaction, trace and state diff (topics, data, etc.).
 #[derive(Serialize)]
 pub(crate) struct WorkerQuery {
     #[serde(rename = "fromBlock")]
@@ -21,48 +89,51 @@ pub(crate) struct WorkerQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transactions: Option<Vec<TransactionsFilter>>, // Filters for transactions based on sender, receiver, etc.
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub traces: Option<Vec<TracesFilter>>, // Filters for traces based on type, caller, callee, etc.
+    #[serde(rename = "stateDiffs", skip_serializing_if = "Option::is_none")]
+    pub state_diffs: Option<Vec<StateDiffsFilter>>, // Filters for state diffs based on address, key and kind.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub fields: Option<Fields>, // Specifies which fields (topics, data, etc.) to retrieve.
+    #[serde(
+        rename = "includeAllBlocks",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub include_all_blocks: bool, // Returns every block header in the range, even without matching items.
 }
 
 impl WorkerQuery {
-    /// Creates a new `WorkerQuery` from log and transaction filters, as well as options for which fields to retrieve.
+    /// Creates a new `WorkerQuery` from the filters and field selections of a `QuerySet`.
     ///
     /// # Arguments
     ///
     /// * `from_block` - The starting block number for the query.
     /// * `to_block` - The optional ending block number.
-    /// * `log_filters` - A list of log filters to be applied.
-    /// * `tx_filters` - A list of transaction filters to be applied.
-    /// * `log_options` - Options for selecting log fields (topics, data, etc.).
-    /// * `tx_options` - Options for selecting transaction fields (hash, gas, etc.).
+    /// * `query` - The log, transaction, trace and state diff filters and the fields to select for each.
     ///
     /// # Returns
     ///
     /// A `WorkerQuery` instance ready to be sent to the worker.
-    pub fn from_filters(
-        from_block: u64,
-        to_block: Option<u64>,
-        log_filters: &[LogFilter],
-        tx_filters: &[TransactionFilter],
-        log_options: &Option<LogFields>,
-        tx_options: &Option<TransactionFields>,
-    ) -> Self {
-        let fields = if log_options.is_some() || tx_options.is_some() {
+    pub fn from_filters(from_block: u64, to_block: Option<u64>, query: &QuerySet) -> Self {
+        let block_options = &query.block_options;
+        let log_options = &query.log_options;
+        let tx_options = &query.tx_options;
+        let trace_options = &query.trace_options;
+        let state_diff_options = &query.state_diff_options;
+
+        let fields = if block_options.is_some()
+            || log_options.is_some()
+            || tx_options.is_some()
+            || trace_options.is_some()
+            || state_diff_options.is_some()
+        {
             Some(Fields {
-                log: log_options.as_ref().map(|opts| {
-                    let mut log_map = HashMap::new();
-                    log_map.insert("topics".to_string(), opts.topic0);
-                    log_map.insert("data".to_string(), opts.data);
-                    log_map
-                }),
-                transaction: tx_options.as_ref().map(|opts| {
-                    let mut tx_map = HashMap::new();
-                    tx_map.insert("hash".to_string(), opts.hash);
-                    tx_map.insert("to".to_string(), opts.to);
-                    tx_map.insert("from".to_string(), opts.from);
-                    // Add more options as needed
-                    tx_map
-                }),
+                block: block_options.as_ref().map(BlockFields::to_field_map),
+                log: log_options.as_ref().map(LogFields::to_field_map),
+                transaction: tx_options.as_ref().map(TransactionFields::to_field_map),
+                trace: trace_options.as_ref().map(TraceFields::to_field_map),
+                state_diff: state_diff_options
+                    .as_ref()
+                    .map(StateDiffFields::to_field_map),
             })
         } else {
             None
@@ -71,27 +142,200 @@ impl WorkerQuery {
         Self {
             from_block,
             to_block,
-            logs: if !log_filters.is_empty() {
+            logs: if !query.log_filters.is_empty() {
+                Some(query.log_filters.iter().map(LogsFilter::from).collect())
+            } else {
+                Some(vec![])
+            },
+            transactions: if !query.tx_filters.is_empty() {
                 Some(
-                    log_filters
+                    query
+                        .tx_filters
                         .iter()
-                        .map(|filter| LogsFilter::from(filter))
+                        .map(TransactionsFilter::from)
                         .collect(),
                 )
             } else {
-                Some(vec![])
+                None
             },
-            transactions: if !tx_filters.is_empty() {
+            traces: if !query.trace_filters.is_empty() {
+                Some(query.trace_filters.iter().map(TracesFilter::from).collect())
+            } else {
+                None
+            },
+            state_diffs: if !query.state_diff_filters.is_empty() {
                 Some(
-                    tx_filters
+                    query
+                        .state_diff_filters
                         .iter()
-                        .map(|filter| TransactionsFilter::from(filter))
+                        .map(StateDiffsFilter::from)
                         .collect(),
                 )
             } else {
                 None
             },
             fields,
+            include_all_blocks: query.include_all_blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// A setter for a single field flag, paired with the wire name it should produce.
+    type FlagCase<T> = (fn(&mut T), &'static str);
+
+    /// Serializes the `fields` object of a query built from `query`.
+    fn fields_json(query: &QuerySet) -> Value {
+        let query = WorkerQuery::from_filters(0, Some(1), query);
+        serde_json::to_value(&query).unwrap()["fields"].clone()
+    }
+
+    /// Test that every log field flag is sent under its wire name.
+    #[test]
+    fn test_log_field_flags() {
+        let cases: [FlagCase<LogFields>; 9] = [
+            (|f| f.topic0 = true, "topics"),
+            (|f| f.data = true, "data"),
+            (|f| f.transaction_index = true, "transactionIndex"),
+            (|f| f.log_index = true, "logIndex"),
+            (|f| f.address = true, "address"),
+            (|f| f.block_number = true, "blockNumber"),
+            (|f| f.block_hash = true, "blockHash"),
+            (|f| f.transaction_hash = true, "transactionHash"),
+            (|f| f.removed = true, "removed"),
+        ];
+        for (select, wire_name) in cases {
+            let mut fields = LogFields::default();
+            select(&mut fields);
+            let query = QuerySet {
+                log_options: Some(fields),
+                ..Default::default()
+            };
+            assert_eq!(fields_json(&query), json!({ "log": { wire_name: true } }));
+        }
+    }
+
+    /// Test that every transaction field flag is sent under its wire name.
+    #[test]
+    fn test_transaction_field_flags() {
+        let cases: [FlagCase<TransactionFields>; 28] = [
+            (|f| f.hash = true, "hash"),
+            (|f| f.nonce = true, "nonce"),
+            (|f| f.transaction_index = true, "transactionIndex"),
+            (|f| f.to = true, "to"),
+            (|f| f.from = true, "from"),
+            (|f| f.value = true, "value"),
+            (|f| f.gas = true, "gas"),
+            (|f| f.gas_price = true, "gasPrice"),
+            (|f| f.input = true, "input"),
+            (|f| f.block_number = true, "blockNumber"),
+            (|f| f.block_hash = true, "blockHash"),
+            (|f| f.max_fee_per_gas = true, "maxFeePerGas"),
+            (
+                |f| f.max_priority_fee_per_gas = true,
+                "maxPriorityFeePerGas",
+            ),
+            (|f| f.tx_type = true, "type"),
+            (|f| f.status = true, "status"),
+            (|f| f.gas_used = true, "gasUsed"),
+            (|f| f.cumulative_gas_used = true, "cumulativeGasUsed"),
+            (|f| f.effective_gas_price = true, "effectiveGasPrice"),
+            (|f| f.contract_address = true, "contractAddress"),
+            (|f| f.chain_id = true, "chainId"),
+            (|f| f.v = true, "v"),
+            (|f| f.r = true, "r"),
+            (|f| f.s = true, "s"),
+            (|f| f.y_parity = true, "yParity"),
+            (|f| f.access_list = true, "accessList"),
+            (|f| f.sighash = true, "sighash"),
+            (|f| f.max_fee_per_blob_gas = true, "maxFeePerBlobGas"),
+            (|f| f.blob_versioned_hashes = true, "blobVersionedHashes"),
+        ];
+        for (select, wire_name) in cases {
+            let mut fields = TransactionFields::default();
+            select(&mut fields);
+            let query = QuerySet {
+                tx_options: Some(fields),
+                ..Default::default()
+            };
+            assert_eq!(
+                fields_json(&query),
+                json!({ "transaction": { wire_name: true } })
+            );
         }
     }
+
+    /// Test that unselected fields are omitted and the map is serialized in a stable order.
+    #[test]
+    fn test_field_map_serialization_order() {
+        let query = QuerySet {
+            tx_options: Some(TransactionFields {
+                value: true,
+                gas_price: true,
+                hash: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let query = WorkerQuery::from_filters(0, Some(1), &query);
+        let body = serde_json::to_string(&query.fields).unwrap();
+        assert_eq!(
+            body,
+            r#"{"transaction":{"gasPrice":true,"hash":true,"value":true}}"#
+        );
+    }
+
+    /// Test that patches run in order and that the range always overrides the body.
+    #[test]
+    fn test_query_body_patches_and_range() {
+        let mut query = QuerySet {
+            raw_body: Some(json!({ "type": "evm", "fromBlock": 1, "toBlock": 2, "logs": [] })),
+            ..Default::default()
+        };
+        query.patches.push(Arc::new(|body: &mut Value| {
+            body["fields"] = json!({ "log": { "data": true } });
+        }));
+        query.patches.push(Arc::new(|body: &mut Value| {
+            body["fields"]["log"]["logIndex"] = true.into();
+            body["toBlock"] = 5.into();
+        }));
+
+        let body = query.body(100, Some(200)).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "type": "evm",
+                "fromBlock": 100,
+                "toBlock": 200,
+                "logs": [],
+                "fields": { "log": { "data": true, "logIndex": true } }
+            })
+        );
+        assert!(query.body(100, None).unwrap().get("toBlock").is_none());
+
+        query.patches.clear();
+        query.raw_body = Some(json!([]));
+        assert!(matches!(
+            query.body(100, Some(200)),
+            Err(DataStreamError::ConfigurationError(_))
+        ));
+    }
+
+    /// Test that without a raw body the body matches the query built from the filters.
+    #[test]
+    fn test_query_body_from_filters() {
+        let query = QuerySet {
+            log_options: Some(LogFields {
+                data: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let expected = serde_json::to_value(WorkerQuery::from_filters(7, Some(9), &query)).unwrap();
+        assert_eq!(query.body(7, Some(9)).unwrap(), expected);
+    }
 }
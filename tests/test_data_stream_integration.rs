@@ -16,7 +16,9 @@ async fn test_data_stream_integration() {
         .add_log_filter(
             LogFilter::new()
                 .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
-                .with_topic("Transfer(address,address,uint256)"),
+                .expect("Invalid address")
+                .with_topic("Transfer(address,address,uint256)")
+                .expect("Invalid topic"),
         )
         .from_block(20_000_000)
         .to_block(20_000_100)
@@ -22,10 +22,14 @@ async fn test_filter_handling() {
         .add_log_filter(
             LogFilter::new()
                 .with_address("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
-                .with_topic("Transfer(address,address,uint256)"),
+                .expect("Invalid address")
+                .with_topic("Transfer(address,address,uint256)")
+                .expect("Invalid topic"),
         )
         .add_tx_filter(
-            TransactionFilter::new().with_from("0x742d35cc6634c0532925a3b844bc454e4438f44e"),
+            TransactionFilter::new()
+                .with_from("0x742d35cc6634c0532925a3b844bc454e4438f44e")
+                .expect("Invalid sender address"),
         )
         .from_block(20_000_000)
         .to_block(20_000_100)
@@ -46,7 +50,7 @@ async fn test_filter_handling() {
                     if let Some(transactions) = &item.transactions {
                         for tx in transactions {
                             assert_eq!(
-                                tx.hash.clone().expect("Expected a hash in the response").to_lowercase(),
+                                tx.hash.expect("Expected a hash in the response").to_string(),
                                 "0xbb4b3fc2b746877dce70862850602f1d19bd890ab4db47e6b7ee1da1fe578a0d",
                                 "Transaction sender should match the filter"
                             );